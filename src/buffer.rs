@@ -1,126 +1,2460 @@
-use crate::cursor::Cursor;
+use std::{
+    collections::{HashMap, HashSet},
+    fs::{self, File},
+    io::{self, Read},
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use crate::{
+    config::{Config, Options, Theme},
+    cursor::Cursor,
+    encoding::{self, Decoded, Encoding, Ending, Format},
+    ex,
+    hex::{self, Hex},
+    history::{Edit, History},
+    indent,
+    motion::{self, Motion, Operator},
+    paged::Paged,
+    pair, save,
+    selection::{Kind, Selection},
+    shell, state, swap, unicode,
+    watch::Stamp,
+};
 
 pub struct Buffer {
+    name: String,
+    path: Option<PathBuf>,
     source: String,
+    format: Format,
+    written: Format,
+    escaped: bool,
+    readonly: bool,
+    view: bool,
+    piped: bool,
+    paged: Option<Paged>,
+    hex: Option<Hex>,
+    changed: Option<Instant>,
+    swapped: bool,
+    stamp: Option<Stamp>,
     cursor: Cursor,
+    cursors: Vec<Cursor>,
     mode: Mode,
-    scale: Option<u32>,
+    operator: Option<(Operator, Option<usize>)>,
+    visual: Option<Selection>,
+    reselect: Option<(Kind, usize, usize)>,
+    session: Option<Session>,
+    batch: Option<(Vec<Edit>, usize)>,
+    last: Option<Change>,
+    register: String,
+    message: Option<String>,
+    history: History,
+    marks: HashMap<char, usize>,
+    options: Options,
+    theme: Theme,
+    top: usize,
 }
 
-enum Mode {
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Mode {
     Graphemes,
     Lines,
 }
 
+#[derive(Debug, Clone, PartialEq)]
+enum Change {
+    Operator {
+        operator: Operator,
+        action: Action,
+        count: Option<usize>,
+        argument: Option<char>,
+    },
+    Action {
+        action: Action,
+        count: Option<usize>,
+        argument: Option<char>,
+    },
+    Insert(Box<Change>, String),
+}
+
+struct Session {
+    entry: Option<Change>,
+    text: String,
+    edits: Vec<Edit>,
+    cursor: usize,
+    replace: bool,
+    replaced: Vec<(usize, Option<String>)>,
+}
+
 use unicode_segmentation::UnicodeSegmentation;
 
 impl Buffer {
     pub fn new(input: impl Into<String>) -> Self {
+        let config = Config::default();
+
         Self {
+            name: "[No Name]".into(),
+            path: None,
             source: input.into(),
+            format: Format::default(),
+            written: Format::default(),
+            escaped: false,
+            readonly: false,
+            view: false,
+            piped: false,
+            paged: None,
+            hex: None,
+            changed: None,
+            swapped: false,
+            stamp: None,
             cursor: Cursor::default(),
-            mode: Mode::Graphemes,
-            scale: None,
+            cursors: vec![],
+            mode: config.options.lens,
+            operator: None,
+            visual: None,
+            reselect: None,
+            session: None,
+            batch: None,
+            last: None,
+            register: String::new(),
+            message: None,
+            history: History::default(),
+            marks: HashMap::new(),
+            options: config.options,
+            theme: config.theme,
+            top: 0,
+        }
+    }
+
+    pub fn open(path: &Path, options: &Options) -> io::Result<Self> {
+        let (source, stamp, view) = match File::open(path) {
+            Ok(mut file) => {
+                let metadata = file.metadata()?;
+
+                let large = options.large_file.checked_mul(1 << 20);
+
+                if large.is_some_and(|large| large > 0 && metadata.len() >= large) {
+                    return Buffer::page(path, file);
+                }
+
+                let mut source = Vec::with_capacity(metadata.len() as usize);
+
+                file.read_to_end(&mut source)?;
+
+                let stamp = Stamp::new(&metadata, &source);
+
+                let view = matches!(
+                    File::options().append(true).open(path),
+                    Err(error) if error.kind() == io::ErrorKind::PermissionDenied
+                );
+
+                (source, Some(stamp), view)
+            }
+            Err(error) if error.kind() == io::ErrorKind::NotFound => (vec![], None, false),
+            Err(error) => return Err(error),
+        };
+
+        let mut buffer = Buffer::decoded(source);
+
+        buffer.stamp = stamp;
+        buffer.view = view;
+        buffer.rename(path);
+
+        match swap::read(path) {
+            Ok(Some(bytes)) if bytes == buffer.source.as_bytes() => {
+                let _ = swap::remove(path);
+            }
+            Ok(Some(_)) => {
+                let found = "Swap file found: :recover, :recover diff or :recover delete";
+
+                buffer.message = Some(match buffer.message.take() {
+                    Some(message) => format!("{}\n{}", message, found),
+                    None => found.into(),
+                });
+            }
+            _ => {}
+        }
+
+        Ok(buffer)
+    }
+
+    fn decoded(bytes: Vec<u8>) -> Self {
+        let (mut format, decoded) = encoding::decode(bytes);
+
+        let (text, escaped, readonly, message) = match decoded {
+            Decoded::Valid(text) => (text, false, false, None),
+            Decoded::Escaped(text, invalid) => (
+                text,
+                true,
+                false,
+                Some(format!(
+                    "{} invalid UTF-8 bytes shown as \\xNN and kept on save",
+                    invalid
+                )),
+            ),
+            Decoded::Lossy(text) => (
+                text,
+                false,
+                true,
+                Some(format!(
+                    "Invalid {} replaced, buffer is read-only",
+                    format.encoding.name()
+                )),
+            ),
+        };
+
+        let (text, ending, mixed) = encoding::normalize(text);
+        let mut buffer = Buffer::new(text);
+
+        buffer.escaped = escaped;
+        buffer.readonly = readonly;
+
+        if let Some(message) = message {
+            buffer.notify(message);
+        } else if mixed {
+            buffer.notify(format!(
+                "Mixed line endings, will be saved as {}",
+                ending.name()
+            ));
+        }
+
+        format.ending = ending;
+        buffer.format = format;
+        buffer.written = format;
+
+        buffer
+    }
+
+    fn page(path: &Path, file: File) -> io::Result<Self> {
+        let paged = Paged::open(file)?;
+
+        let mut buffer = Buffer::new("");
+
+        buffer.notify(format!(
+            "{} MiB opened read-only, editing is disabled",
+            paged.len() >> 20
+        ));
+        buffer.paged = Some(paged);
+        buffer.readonly = true;
+        buffer.rename(path);
+
+        Ok(buffer)
+    }
+
+    pub fn stdin(bytes: Vec<u8>) -> Self {
+        let mut buffer = Buffer::decoded(bytes);
+
+        buffer.name = "[stdin]".into();
+        buffer.piped = true;
+
+        buffer
+    }
+
+    pub fn scratch(name: &str, input: impl Into<String>) -> Self {
+        let mut buffer = Buffer::new(input);
+
+        buffer.name = name.into();
+
+        buffer
+    }
+
+    fn rename(&mut self, path: &Path) {
+        self.name = std::env::current_dir()
+            .ok()
+            .and_then(|directory| path.strip_prefix(directory).ok())
+            .unwrap_or(path)
+            .display()
+            .to_string();
+
+        self.path = Some(path.into());
+    }
+
+    pub fn save(&mut self, path: Option<&Path>, force: bool) -> io::Result<usize> {
+        let path = match (path, &self.path) {
+            (Some(path), _) => path.to_path_buf(),
+            (None, Some(path)) => path.clone(),
+            (None, None) => {
+                return Err(io::Error::new(io::ErrorKind::InvalidInput, "No file name"))
+            }
+        };
+
+        if self.paged.is_some() {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "Large files cannot be written",
+            ));
+        }
+
+        if self.readonly && self.hex.is_none() && self.path.as_deref() == Some(&path) {
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                "Buffer is read-only",
+            ));
+        }
+
+        if self.view && !force && self.path.as_deref() == Some(&path) {
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                "File is read-only (add ! to override)",
+            ));
+        }
+
+        let outdated = match (self.stamp, self.path.as_deref() == Some(&path)) {
+            (Some(stamp), true) => !stamp.current(&path).unwrap_or(true),
+            _ => false,
+        };
+
+        if outdated && !force {
+            return Err(io::Error::other(
+                "File changed on disk since it was read (add ! to override)",
+            ));
+        }
+
+        let (written, stamp) = {
+            let bytes = self.encoded()?;
+
+            save::write(&path, &bytes, self.options.backup)?;
+
+            let stamp = fs::metadata(&path).map(|metadata| Stamp::new(&metadata, &bytes));
+
+            (bytes.len(), stamp.ok())
+        };
+
+        if self.path.is_none() || self.path.as_deref() == Some(&path) {
+            self.rename(&path);
+            self.saved();
+            self.stamp = stamp;
+            self.view = false;
+
+            if std::mem::take(&mut self.swapped) {
+                let _ = swap::remove(&path);
+            }
+        }
+
+        Ok(written)
+    }
+
+    pub fn stage(&mut self) -> io::Result<Vec<u8>> {
+        let bytes = self.encoded()?.into_owned();
+
+        self.saved();
+
+        Ok(bytes)
+    }
+
+    fn saved(&mut self) {
+        self.history.save();
+        self.written = self.format;
+        self.changed = None;
+
+        if let Some(hex) = self.hex.as_mut() {
+            hex.save();
+        }
+    }
+
+    fn encoded(&self) -> io::Result<Cow<'_, [u8]>> {
+        match &self.hex {
+            Some(hex) => Ok(Cow::Borrowed(hex.bytes())),
+            None => encoding::encode(&self.source, self.format, self.escaped).map_err(|count| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!(
+                        "{} characters cannot be encoded as {}",
+                        count,
+                        self.format.encoding.name()
+                    ),
+                )
+            }),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    pub fn protect(&mut self) {
+        self.view = true;
+    }
+
+    pub fn piped(&self) -> bool {
+        self.piped
+    }
+
+    pub fn modified(&self) -> bool {
+        self.history.modified()
+            || self.format != self.written
+            || self.hex.as_ref().is_some_and(Hex::modified)
+            || self
+                .session
+                .as_ref()
+                .is_some_and(|session| !session.edits.is_empty())
+    }
+
+    pub fn current(&self) -> &str {
+        UnicodeSegmentation::graphemes(&self.source[..], true)
+            .next()
+            .unwrap_or("")
+    }
+
+    pub fn cursor(&self) -> &Cursor {
+        &self.cursor
+    }
+
+    pub fn input_mode(&self) -> keymap::Mode {
+        if self.hex.as_ref().is_some_and(Hex::typing) {
+            return keymap::Mode::Insert;
+        }
+
+        match (&self.session, &self.visual) {
+            (Some(_), _) => keymap::Mode::Insert,
+            (None, Some(_)) => keymap::Mode::Visual,
+            (None, None) if self.hex.is_some() => keymap::Mode::Hex,
+            (None, None) => keymap::Mode::Normal,
+        }
+    }
+
+    pub fn notify(&mut self, message: impl Into<String>) {
+        self.message = Some(message.into());
+    }
+
+    pub fn message(&self) -> Option<&str> {
+        self.message.as_deref()
+    }
+
+    pub fn blank(&self) -> bool {
+        self.path.is_none() && !self.piped && self.source.is_empty() && !self.modified()
+    }
+
+    pub fn poll(&mut self) -> bool {
+        if let Some(paged) = self.paged.as_mut() {
+            return paged.poll();
+        }
+
+        let delay = Duration::from_millis(self.options.update_time);
+
+        let path = match (&self.path, self.changed) {
+            (Some(path), Some(changed)) if changed.elapsed() >= delay => path.clone(),
+            _ => return false,
+        };
+
+        self.changed = None;
+
+        let bytes = match &self.hex {
+            Some(hex) => hex.bytes(),
+            None => self.source.as_bytes(),
+        };
+
+        match swap::write(&path, bytes) {
+            Ok(()) => {
+                self.swapped = true;
+
+                false
+            }
+            Err(error) => {
+                self.notify(format!("Swap file not written: {}", error));
+
+                true
+            }
+        }
+    }
+
+    pub fn refresh(&mut self) -> bool {
+        let (path, stamp) = match (&self.path, self.stamp) {
+            (Some(path), Some(stamp)) => (path.clone(), stamp),
+            _ => return false,
+        };
+
+        match stamp.current(&path) {
+            Ok(true) => false,
+            Ok(false) if self.session.is_none() && self.hex.is_none() && !self.modified() => {
+                let message = match self.reload() {
+                    Ok(()) => format!("\"{}\" reloaded", self.name),
+                    Err(error) => error.to_string(),
+                };
+
+                self.notify(message);
+
+                true
+            }
+            Ok(false) => {
+                self.notify(format!(
+                    "\"{}\" changed on disk: :reload, :reload keep or :reload diff",
+                    self.name
+                ));
+
+                true
+            }
+            Err(error) if error.kind() == io::ErrorKind::NotFound => {
+                self.stamp = None;
+                self.notify(format!("\"{}\" deleted on disk", self.name));
+
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    pub fn reload(&mut self) -> io::Result<()> {
+        let path = self
+            .path
+            .clone()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "No file name"))?;
+
+        if self.hex.is_some() {
+            return Err(io::Error::other("Leave hex mode before reloading"));
+        }
+
+        let fresh = Buffer::open(&path, &self.options)?;
+
+        if fresh.paged.is_some() {
+            return Err(io::Error::other("File is too large to reload"));
+        }
+
+        self.stamp = fresh.stamp;
+        self.assume(fresh);
+        self.history.save();
+        self.written = self.format;
+        self.changed = None;
+
+        Ok(())
+    }
+
+    fn assume(&mut self, fresh: Buffer) {
+        self.visual = None;
+        self.cursors.clear();
+
+        if fresh.source != self.source {
+            self.change(0..self.source.len(), &fresh.source);
+        }
+
+        self.cursor = Cursor::default()
+            .seek(&self.source, self.cursor.z())
+            .settle(&self.source);
+        self.format = fresh.format;
+        self.escaped = fresh.escaped;
+        self.readonly = fresh.readonly;
+        self.view |= fresh.view;
+        self.message = fresh.message;
+    }
+
+    fn toggle_hex(&mut self) -> Result<(), String> {
+        if let Some(hex) = self.hex.take() {
+            let saved = !hex.modified() && !self.history.modified();
+
+            self.assume(Buffer::decoded(hex.into_bytes()));
+
+            if saved {
+                self.history.save();
+                self.written = self.format;
+            }
+
+            return Ok(());
+        }
+
+        let unencodable = |count| {
+            format!(
+                "{} characters cannot be encoded as {}",
+                count,
+                self.format.encoding.name()
+            )
+        };
+
+        let bytes = match (&self.path, self.readonly && !self.history.modified()) {
+            (Some(path), true) => fs::read(path).map_err(|error| error.to_string())?,
+            _ => encoding::encode(&self.source, self.format, self.escaped)
+                .map_err(unencodable)?
+                .into_owned(),
+        };
+
+        let offset = encoding::encode(&self.source[..self.cursor.z()], self.format, self.escaped)
+            .map_or(0, |bytes| bytes.len());
+
+        self.visual = None;
+        self.cursors.clear();
+        self.hex = Some(Hex::new(bytes, offset));
+
+        Ok(())
+    }
+
+    pub fn keep(&mut self) -> io::Result<()> {
+        if let Some(path) = &self.path {
+            self.stamp = Some(Stamp::read(path)?);
+        }
+
+        Ok(())
+    }
+
+    pub fn release(&mut self) {
+        if let (Some(path), true) = (&self.path, std::mem::take(&mut self.swapped)) {
+            let _ = swap::remove(path);
+        }
+    }
+
+    pub fn state(&self) -> Option<(PathBuf, state::Entry)> {
+        let mut marks = self
+            .marks
+            .iter()
+            .map(|(mark, z)| (*mark, *z))
+            .collect::<Vec<_>>();
+
+        marks.sort();
+
+        Some((
+            self.path.clone()?,
+            state::Entry {
+                offset: self.cursor.z(),
+                lens: self.mode,
+                marks,
+            },
+        ))
+    }
+
+    pub fn restore(&mut self, entry: &state::Entry) {
+        self.mode = entry.lens;
+
+        if self.paged.is_some() {
+            return;
+        }
+
+        self.marks = entry
+            .marks
+            .iter()
+            .filter(|(_, z)| self.source.is_char_boundary(*z))
+            .copied()
+            .collect();
+
+        let offset = (0..=entry.offset.min(self.source.len()))
+            .rev()
+            .find(|z| self.source.is_char_boundary(*z))
+            .unwrap_or(0);
+
+        self.cursor = Cursor::default()
+            .seek(&self.source, offset)
+            .settle(&self.source);
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    pub fn recover(&mut self, bytes: Vec<u8>) -> Result<(), String> {
+        match self.hex.as_mut() {
+            Some(hex) => hex.restore(&bytes)?,
+            None => {
+                let text = String::from_utf8(bytes)
+                    .map_err(|_| "Swap file holds raw bytes, recover it in hex mode")?;
+
+                self.change(0..self.source.len(), &text);
+                self.cursor = Cursor::default()
+                    .seek(&self.source, self.cursor.z())
+                    .settle(&self.source);
+            }
+        }
+
+        self.swapped = true;
+        self.notify("Recovered from swap file, write to keep the changes");
+
+        Ok(())
+    }
+
+    pub fn append(&mut self, text: &str) {
+        self.source.push_str(text);
+    }
+
+    pub fn configure(&mut self, config: &Config) {
+        self.options = config.options;
+        self.theme = config.theme;
+        self.mode = config.options.lens;
+    }
+
+    pub fn scroll(&mut self, rows: usize) {
+        let height = rows.saturating_sub(2).max(1);
+        let scroll_off = self.options.scroll_off.min(height.saturating_sub(1) / 2);
+
+        if let Some(hex) = self.hex.as_mut() {
+            return hex.scroll(height, scroll_off);
+        }
+        let y = self.cursor.y();
+
+        if y < self.top + scroll_off {
+            self.top = y.saturating_sub(scroll_off);
+        } else if y + scroll_off >= self.top + height {
+            self.top = y + scroll_off + 1 - height;
+        }
+
+        let lines = match &self.paged {
+            Some(paged) => paged.lines(),
+            None => motion::line_count(&self.source),
+        };
+
+        self.top = self.top.min(lines - 1);
+    }
+
+    pub fn goto(&mut self, z: usize) {
+        self.cursor = Motion::Offset.apply(&self.cursor, &self.source, Some(z + 1));
+    }
+}
+
+use crate::keymap::{self, Action};
+
+impl Buffer {
+    pub fn perform(
+        &mut self,
+        action: Action,
+        count: Option<usize>,
+        argument: Option<char>,
+    ) -> bool {
+        self.message = None;
+
+        if self.paged.is_some() {
+            return self.browse(action, count);
+        }
+
+        if let Some(hex) = self.hex.as_mut() {
+            let done = hex.perform(action, count, argument);
+
+            if hex.modified() {
+                self.touch();
+            }
+
+            return match done {
+                Ok(done) => done,
+                Err(error) => {
+                    self.notify(error);
+
+                    false
+                }
+            };
+        }
+
+        if self.visual.is_some() {
+            if let Some(done) = self.select(action, count) {
+                return done;
+            }
+        }
+
+        let change = match (self.operator, action) {
+            (_, Action::Cancel | Action::Prompt) => None,
+            (Some((operator, scale)), action) if operator != Operator::Yank => {
+                Some(Change::Operator {
+                    operator,
+                    action,
+                    count: match (scale, count) {
+                        (Some(scale), Some(count)) => Some(scale * count),
+                        (scale, count) => count.or(scale),
+                    },
+                    argument,
+                })
+            }
+            (
+                None,
+                Action::ToggleCase
+                | Action::Insert
+                | Action::Append
+                | Action::InsertStart
+                | Action::AppendEnd
+                | Action::OpenBelow
+                | Action::OpenAbove
+                | Action::Replace
+                | Action::ReplaceChar,
+            ) => Some(Change::Action {
+                action,
+                count,
+                argument,
+            }),
+            _ => None,
+        };
+
+        let done = match self.visual.is_none() && per_cursor(action) {
+            true => self.lockstep(|buffer| buffer.act(action, count, argument)),
+            false => self.act(action, count, argument),
+        };
+
+        if done && self.operator.is_none() && change.is_some() {
+            match self.session.as_mut() {
+                Some(session) => session.entry = change,
+                None => self.last = change,
+            };
+        }
+
+        done
+    }
+
+    fn act(&mut self, action: Action, count: Option<usize>, argument: Option<char>) -> bool {
+        match action {
+            Action::Left => self.motion(Motion::Left, count),
+            Action::Down | Action::Open => self.motion(Motion::Down, count),
+            Action::Up => self.motion(Motion::Up, count),
+            Action::Right => self.motion(Motion::Right, count),
+            Action::First => self.motion(Motion::First, count),
+            Action::Last => self.motion(Motion::Last, count),
+            Action::Offset => self.motion(Motion::Offset, count),
+            Action::Pair => match count {
+                Some(_) => self.motion(Motion::Percent, count),
+                None => self.motion(Motion::Pair, count),
+            },
+            Action::NextHeading => self.motion(Motion::NextHeading, count),
+            Action::PreviousHeading => self.motion(Motion::PreviousHeading, count),
+            Action::Delete => self.operator(Operator::Delete, count),
+            Action::Yank => self.operator(Operator::Yank, count),
+            Action::Change => self.operator(Operator::Change, count),
+            Action::ShiftRight => self.operator(Operator::ShiftRight, count),
+            Action::ShiftLeft => self.operator(Operator::ShiftLeft, count),
+            Action::ToggleCase => self.toggle_case(count),
+            Action::Insert
+            | Action::Append
+            | Action::InsertStart
+            | Action::AppendEnd
+            | Action::OpenBelow
+            | Action::OpenAbove
+            | Action::Replace => self.enter(action),
+            Action::ReplaceChar => self.replace(argument, count.unwrap_or(1)),
+            Action::Newline => self.newline(),
+            Action::Backspace => self.backspace(),
+            Action::Remove => self.remove(),
+            Action::Visual => self.start(Kind::Charwise),
+            Action::VisualLine => self.start(Kind::Linewise),
+            Action::VisualBlock => self.start(Kind::Blockwise),
+            Action::Reselect => self.reselect(),
+            Action::AddCursor => self.add_cursor(true),
+            Action::SkipCursor => self.add_cursor(false),
+            Action::RemoveCursor => match self.cursors.pop() {
+                Some(cursor) => {
+                    self.cursor = cursor;
+
+                    true
+                }
+                None => false,
+            },
+            Action::Undo => self.undo(),
+            Action::Redo => self.redo(),
+            Action::Repeat => self.repeat(count),
+            Action::Mark => match argument.filter(char::is_ascii_lowercase) {
+                Some(mark) => {
+                    self.marks.insert(mark, self.cursor.z());
+
+                    true
+                }
+                None => false,
+            },
+            Action::Jump => match argument.and_then(|mark| self.marks.get(&mark)) {
+                Some(z) => {
+                    let line = self.source[..self.source.floor_char_boundary(*z)]
+                        .matches('\n')
+                        .count();
+
+                    self.motion(Motion::Line(line + 1), None)
+                }
+                None => {
+                    self.operator = None;
+                    self.notify(format!("Mark not set: {}", argument.unwrap_or(' ')));
+
+                    false
+                }
+            },
+            Action::Lens => {
+                self.mode = match self.mode {
+                    Mode::Graphemes => Mode::Lines,
+                    Mode::Lines => Mode::Graphemes,
+                };
+
+                true
+            }
+            Action::Cancel => {
+                if self.operator.take().is_none() && self.session.is_none() && self.visual.is_none()
+                {
+                    self.cursors.clear();
+                }
+
+                self.leave();
+
+                true
+            }
+            Action::Prompt => {
+                self.leave();
+
+                true
+            }
+            _ => true,
+        }
+    }
+
+    fn browse(&mut self, action: Action, count: Option<usize>) -> bool {
+        let y = self.cursor.y();
+
+        let line = match action {
+            Action::Down | Action::Open => y.saturating_add(count.unwrap_or(1)),
+            Action::Up => y.saturating_sub(count.unwrap_or(1)),
+            Action::First => count.unwrap_or(1).saturating_sub(1),
+            Action::Last => count.map_or(usize::MAX, |count| count.saturating_sub(1)),
+            Action::Left | Action::Right | Action::Cancel | Action::Prompt => return true,
+            Action::Lens => return self.act(action, count, None),
+            _ => {
+                self.notify("Not available for large files");
+
+                return false;
+            }
+        };
+
+        self.view(line)
+    }
+
+    fn view(&mut self, line: usize) -> bool {
+        let paged = match &self.paged {
+            Some(paged) => paged,
+            None => return false,
+        };
+
+        let last = paged.lines() - 1;
+        let indexing = line > last && !paged.done();
+
+        self.cursor = (0, line.min(last), paged.offset(line)).into();
+
+        if indexing {
+            self.notify(format!("Still indexing, {} lines so far", last + 1));
+        }
+
+        true
+    }
+
+    fn lockstep(&mut self, mut step: impl FnMut(&mut Self) -> bool) -> bool {
+        if self.cursors.is_empty() {
+            return step(self);
+        }
+
+        let operator = self.operator;
+
+        if self.batch.is_none() && self.session.is_none() {
+            self.batch = Some((vec![], self.cursor.z()));
+        }
+
+        let mut done = step(self);
+
+        let text = self.session.as_ref().map(|session| session.text.clone());
+
+        for index in 0..self.cursors.len() {
+            self.operator = operator;
+
+            std::mem::swap(&mut self.cursor, &mut self.cursors[index]);
+            done |= step(self);
+            std::mem::swap(&mut self.cursor, &mut self.cursors[index]);
+        }
+
+        if let (Some(session), Some(text)) = (self.session.as_mut(), text) {
+            session.text = text;
+        }
+
+        if let Some((edits, cursor)) = self.batch.take() {
+            match self.session.as_mut() {
+                Some(session) => session.edits.extend(edits),
+                None => self.history.record(edits, cursor),
+            };
+        }
+
+        self.merge();
+
+        done
+    }
+
+    fn merge(&mut self) {
+        let mut seen = HashSet::from([self.cursor.z()]);
+
+        self.cursors.retain(|cursor| seen.insert(cursor.z()));
+    }
+
+    fn add_cursor(&mut self, keep: bool) -> bool {
+        let z = self.cursor.z();
+
+        let word = self
+            .source
+            .split_word_bound_indices()
+            .find(|(index, word)| (*index..index + word.len()).contains(&z))
+            .filter(|(_, word)| word.chars().any(char::is_alphanumeric));
+
+        let (start, word) = match word {
+            Some(word) => word,
+            None => {
+                self.notify("No word under cursor");
+
+                return false;
+            }
+        };
+
+        let occupied = self
+            .cursors
+            .iter()
+            .chain([&self.cursor])
+            .map(Cursor::z)
+            .collect::<HashSet<_>>();
+
+        let matches = self
+            .source
+            .split_word_bound_indices()
+            .filter(|(_, candidate)| *candidate == word)
+            .map(|(index, _)| index + z - start)
+            .filter(|z| !occupied.contains(z))
+            .collect::<Vec<_>>();
+
+        let next = match matches.iter().find(|next| **next > z).or(matches.first()) {
+            Some(next) => *next,
+            None => {
+                self.notify(format!("No more matches: {}", word));
+
+                return false;
+            }
+        };
+
+        if keep {
+            self.cursors.push(self.cursor.clone());
+        }
+
+        self.cursor = Cursor::default().seek(&self.source, next);
+
+        true
+    }
+
+    fn repeat(&mut self, count: Option<usize>) -> bool {
+        match self.last.clone() {
+            Some(change) => self.replay(change, count),
+            None => false,
+        }
+    }
+
+    fn replay(&mut self, change: Change, count: Option<usize>) -> bool {
+        match change {
+            Change::Operator {
+                operator,
+                action,
+                count: last,
+                argument,
+            } => {
+                self.operator = Some((operator, None));
+
+                self.perform(action, count.or(last), argument)
+            }
+            Change::Action {
+                action,
+                count: last,
+                argument,
+            } => self.perform(action, count.or(last), argument),
+            Change::Insert(entry, text) => {
+                if !self.replay(*entry, count) {
+                    return false;
+                }
+
+                for character in text.chars() {
+                    match character {
+                        '\n' => {
+                            self.lockstep(Self::newline);
+                        }
+                        character => self.insert(character),
+                    };
+                }
+
+                self.leave();
+
+                true
+            }
+        }
+    }
+
+    pub fn insert(&mut self, character: char) {
+        if let Some(hex) = self.hex.as_mut() {
+            let written = hex.write(character);
+
+            if hex.modified() {
+                self.touch();
+            }
+
+            if let Err(error) = written {
+                self.notify(error);
+            }
+
+            return;
+        }
+
+        self.lockstep(|buffer| {
+            buffer.put(character);
+
+            true
+        });
+    }
+
+    fn put(&mut self, character: char) {
+        let z = self.cursor.z();
+
+        let overwrite =
+            character != '\n' && self.session.as_ref().is_some_and(|session| session.replace);
+
+        let length = match self.cursor.current(&self.source) {
+            "\n" | "\r\n" | "" => 0,
+            current if overwrite => current.len(),
+            _ => 0,
+        };
+
+        let original = self.source[z..z + length].to_string();
+
+        self.change(z..z + length, character.encode_utf8(&mut [0; 4]));
+
+        let end = z + character.len_utf8();
+
+        if let Some(session) = self.session.as_mut() {
+            session.text.push(character);
+
+            if overwrite {
+                session
+                    .replaced
+                    .push((end, Some(original).filter(|_| length > 0)));
+            }
+        }
+
+        self.cursor = Cursor::default().seek(&self.source, end);
+    }
+
+    fn replace(&mut self, character: Option<char>, count: usize) -> bool {
+        let (character, z) = match character {
+            Some(character) => (character, self.cursor.z()),
+            None => return false,
+        };
+
+        let graphemes = self.source[z..]
+            .graphemes(true)
+            .take_while(|grapheme| !matches!(*grapheme, "\n" | "\r\n"))
+            .take(count)
+            .map(str::len)
+            .collect::<Vec<_>>();
+
+        if graphemes.len() < count {
+            return false;
+        }
+
+        let span = z..z + graphemes.iter().sum::<usize>();
+
+        let (text, end) = match character {
+            '\n' => ("\n".to_string(), z + 1),
+            character => {
+                let text = character.to_string().repeat(count);
+                let end = z + text.len() - character.len_utf8();
+
+                (text, end)
+            }
+        };
+
+        self.change(span, &text);
+        self.cursor = Cursor::default().seek(&self.source, end);
+
+        true
+    }
+
+    fn enter(&mut self, action: Action) -> bool {
+        let line = self.cursor.z() - self.cursor.x();
+        let end = self.source[line..]
+            .find('\n')
+            .map_or(self.source.len(), |index| line + index);
+
+        self.begin();
+
+        let z = match action {
+            Action::Append => match self.cursor.current(&self.source) {
+                "\n" => self.cursor.z(),
+                current => self.cursor.z() + current.len(),
+            },
+            Action::InsertStart => {
+                let text = &self.source[line..end];
+
+                line + text.len() - text.trim_start().len()
+            }
+            Action::AppendEnd => end,
+            Action::OpenBelow => {
+                let prefix = indent::prefix(&self.source[line..end]).next;
+
+                self.change(end..end, &format!("\n{}", prefix));
+
+                end + 1 + prefix.len()
+            }
+            Action::OpenAbove => {
+                let prefix = indent::leading(&self.source[line..end]).to_string();
+
+                self.change(line..line, &format!("{}\n", prefix));
+
+                line + prefix.len()
+            }
+            Action::Replace => {
+                if let Some(session) = self.session.as_mut() {
+                    session.replace = true;
+                }
+
+                self.cursor.z()
+            }
+            _ => self.cursor.z(),
+        };
+
+        self.cursor = Cursor::default().seek(&self.source, z);
+
+        true
+    }
+
+    fn begin(&mut self) {
+        if self.session.is_none() {
+            self.session = Some(Session {
+                entry: None,
+                text: String::new(),
+                edits: vec![],
+                cursor: self.cursor.z(),
+                replace: false,
+                replaced: vec![],
+            });
+        }
+    }
+
+    fn leave(&mut self) {
+        if let Some(selection) = self.visual.take() {
+            self.remember(selection);
+        }
+
+        if let Some(session) = self.session.take() {
+            self.history.record(session.edits, session.cursor);
+
+            if let Some(entry) = session.entry {
+                self.last = Some(Change::Insert(Box::new(entry), session.text));
+            }
+
+            let source = &self.source;
+
+            for cursor in self.cursors.iter_mut().chain([&mut self.cursor]) {
+                if cursor.x() > 0 {
+                    *cursor = cursor.backward_graphemes(source, 1);
+                }
+
+                *cursor = cursor.settle(source);
+            }
+
+            self.merge();
+        }
+    }
+
+    fn newline(&mut self) -> bool {
+        let z = self.cursor.z();
+        let line = z - self.cursor.x();
+        let end = self.source[line..]
+            .find('\n')
+            .map_or(self.source.len(), |index| line + index);
+
+        let text = &self.source[line..end];
+        let prefix = indent::prefix(&self.source[line..z]);
+
+        if prefix.empty(text) {
+            self.change(line..end, &prefix.outer);
+            self.cursor = Cursor::default().seek(&self.source, line + prefix.outer.len());
+
+            return true;
+        }
+
+        self.put('\n');
+        self.change(z + 1..z + 1, &prefix.next);
+        self.cursor = Cursor::default().seek(&self.source, z + 1 + prefix.next.len());
+
+        true
+    }
+
+    fn backspace(&mut self) -> bool {
+        let z = self.cursor.z();
+
+        let grapheme = match self.source[..z].graphemes(true).next_back() {
+            Some(grapheme) => grapheme.to_string(),
+            None => return false,
+        };
+
+        let mut restored = String::new();
+
+        if let Some(session) = self.session.as_mut() {
+            if session.replace {
+                match session.replaced.iter().rposition(|(end, _)| *end == z) {
+                    Some(index) => {
+                        restored = session.replaced.remove(index).1.unwrap_or_default();
+                    }
+                    None if self.cursor.x() > 0 => {
+                        self.cursor = self.cursor.backward_graphemes(&self.source, 1);
+
+                        return true;
+                    }
+                    None => return false,
+                }
+            }
+
+            if session.text.ends_with(&grapheme) {
+                session.text.truncate(session.text.len() - grapheme.len());
+            }
+        }
+
+        self.change(z - grapheme.len()..z, &restored);
+        self.cursor = Cursor::default().seek(&self.source, z - grapheme.len());
+
+        true
+    }
+
+    fn remove(&mut self) -> bool {
+        let z = self.cursor.z();
+
+        match self.cursor.current(&self.source).len() {
+            0 => false,
+            length => {
+                self.change(z..z + length, "");
+                self.cursor = Cursor::default().seek(&self.source, z);
+
+                true
+            }
+        }
+    }
+
+    fn toggle_case(&mut self, count: Option<usize>) -> bool {
+        let z = self.cursor.z();
+
+        let length = self.source[z..]
+            .graphemes(true)
+            .take_while(|grapheme| *grapheme != "\n")
+            .take(count.unwrap_or(1))
+            .map(str::len)
+            .sum::<usize>();
+
+        if length == 0 {
+            return false;
+        }
+
+        let text = swap_case(&self.source[z..z + length]);
+
+        self.change(z..z + length, &text);
+        self.cursor = Cursor::default()
+            .seek(&self.source, z + text.len())
+            .settle(&self.source);
+
+        true
+    }
+
+    fn start(&mut self, kind: Kind) -> bool {
+        self.operator = None;
+        self.cursors.clear();
+        self.visual = Some(Selection::new(kind, self.cursor.clone()));
+
+        true
+    }
+
+    fn select(&mut self, action: Action, count: Option<usize>) -> Option<bool> {
+        let operator = match action {
+            Action::Delete => Operator::Delete,
+            Action::Yank => Operator::Yank,
+            Action::Change => Operator::Change,
+            Action::ShiftRight => Operator::ShiftRight,
+            Action::ShiftLeft => Operator::ShiftLeft,
+            Action::ToggleCase => Operator::ToggleCase,
+            Action::Swap => {
+                let selection = self.visual.as_mut()?;
+
+                self.cursor = std::mem::replace(&mut selection.anchor, self.cursor.clone());
+
+                return Some(true);
+            }
+            Action::SplitCursors => {
+                let selection = self.visual.take()?;
+                let spans = selection.spans(&self.cursor, &self.source, self.options.tab_width);
+
+                self.remember(selection);
+
+                let mut cursors = spans
+                    .into_iter()
+                    .flat_map(|span| {
+                        self.source[span.clone()].split_inclusive('\n').scan(
+                            span.start,
+                            |offset, line| {
+                                let start = *offset;
+
+                                *offset += line.len();
+
+                                Some(start)
+                            },
+                        )
+                    })
+                    .map(|z| Cursor::default().seek(&self.source, z).settle(&self.source))
+                    .collect::<Vec<_>>();
+
+                self.cursor = cursors.pop()?;
+                self.cursors = cursors;
+                self.merge();
+
+                return Some(true);
+            }
+            Action::Visual | Action::VisualLine | Action::VisualBlock => {
+                let kind = match action {
+                    Action::Visual => Kind::Charwise,
+                    Action::VisualLine => Kind::Linewise,
+                    _ => Kind::Blockwise,
+                };
+
+                match self.visual.as_mut()? {
+                    selection if selection.kind != kind => selection.kind = kind,
+                    _ => self.leave(),
+                };
+
+                return Some(true);
+            }
+            _ => return None,
+        };
+
+        let selection = self.visual.take()?;
+        let spans = selection.spans(&self.cursor, &self.source, self.options.tab_width);
+
+        self.remember(selection);
+
+        match operator {
+            Operator::ShiftRight | Operator::ShiftLeft => {
+                self.shift(&spans, count.unwrap_or(1), operator == Operator::ShiftRight)
+            }
+            operator => self.operate(operator, spans),
+        };
+
+        Some(true)
+    }
+
+    fn remember(&mut self, selection: Selection) {
+        let (start, end) = selection.edges(&self.cursor);
+
+        self.marks.insert('<', start);
+        self.marks.insert('>', end);
+
+        self.reselect = Some((selection.kind, selection.anchor.z(), self.cursor.z()));
+    }
+
+    fn reselect(&mut self) -> bool {
+        let (kind, anchor, z) = match self.reselect {
+            Some(reselect) => reselect,
+            None => return false,
+        };
+
+        let anchor = Cursor::default()
+            .seek(&self.source, anchor)
+            .settle(&self.source);
+
+        self.cursor = Cursor::default().seek(&self.source, z).settle(&self.source);
+        self.visual = Some(Selection::new(kind, anchor));
+
+        true
+    }
+
+    pub fn execute(&mut self, command: &ex::Command) -> Result<(), String> {
+        if let Some(paged) = &self.paged {
+            let address = match (command.name, &command.range) {
+                ("", None) => return Ok(()),
+                (
+                    "",
+                    Some(ex::Range {
+                        start: address,
+                        end: None,
+                        ..
+                    }),
+                ) => address,
+                _ => return Err("Not available for large files".into()),
+            };
+
+            let line = match address.base {
+                ex::Base::Current => self.cursor.y(),
+                ex::Base::Last => paged.lines() - 1,
+                ex::Base::Line(number) => number.saturating_sub(1),
+                _ => return Err("Not available for large files".into()),
+            };
+
+            self.view(line.saturating_add_signed(address.offset));
+
+            return Ok(());
+        }
+
+        let lines = match &command.range {
+            Some(range) => Some(
+                range
+                    .lines(&self.source, self.cursor.y(), &self.marks)
+                    .map_err(|error| error.to_string())?,
+            ),
+            None => None,
+        };
+
+        match (command.name, lines) {
+            ("", Some((_, end))) => {
+                self.motion(Motion::Line(end + 1), None);
+            }
+            ("", None) => {}
+            ("goto", _) => {
+                self.motion(Motion::Offset, command.argument.parse().ok());
+            }
+            (name @ ("delete" | "yank"), lines) => {
+                let (start, end) = lines.unwrap_or((self.cursor.y(), self.cursor.y()));
+                let span = self.lines(start, end);
+
+                match name {
+                    "delete" => self.operate(Operator::Delete, vec![span]),
+                    _ => self.operate(Operator::Yank, vec![span]),
+                };
+            }
+            ("!", Some((start, end))) => {
+                let span = self.lines(start, end);
+
+                let output = shell::run(&command.argument, Some(&self.source[span.clone()]))
+                    .map_err(|error| error.to_string())?;
+
+                let (mut text, ..) = encoding::normalize(output.stdout);
+
+                if !text.is_empty()
+                    && !text.ends_with('\n')
+                    && self.source[span.clone()].ends_with('\n')
+                {
+                    text.push('\n');
+                }
+
+                self.change(span.clone(), &text);
+                self.cursor = Cursor::default()
+                    .seek(&self.source, span.start)
+                    .settle(&self.source);
+
+                self.report(&output.stderr);
+            }
+            ("!", None) => {
+                let output =
+                    shell::run(&command.argument, None).map_err(|error| error.to_string())?;
+
+                self.report(&(output.stdout + &output.stderr));
+            }
+            ("read", lines) => {
+                let line = lines.map_or(self.cursor.y(), |(_, end)| end);
+
+                let (text, stderr) = match command.argument.strip_prefix('!') {
+                    Some(shell_command) => {
+                        let output = shell::run(shell_command.trim(), None)
+                            .map_err(|error| error.to_string())?;
+
+                        (output.stdout, output.stderr)
+                    }
+                    None if command.argument.is_empty() => return Err("No file name".into()),
+                    None => {
+                        let bytes = fs::read(&command.argument)
+                            .map_err(|error| format!("{}: {}", command.argument, error))?;
+
+                        match encoding::decode(bytes) {
+                            (_, Decoded::Valid(text) | Decoded::Escaped(text, _)) => {
+                                (text, String::new())
+                            }
+                            (format, Decoded::Lossy(_)) => {
+                                return Err(format!(
+                                    "{}: invalid {}",
+                                    command.argument,
+                                    format.encoding.name()
+                                ))
+                            }
+                        }
+                    }
+                };
+
+                let (text, ..) = encoding::normalize(text);
+
+                self.insert_below(line, &text);
+                self.report(&stderr);
+            }
+            ("write", lines) => {
+                let (start, end) = lines.unwrap_or((0, motion::line_count(&self.source) - 1));
+                let span = self.lines(start, end);
+
+                let shell_command = command.argument.trim_start_matches('!').trim();
+
+                let output = shell::run(shell_command, Some(&self.source[span]))
+                    .map_err(|error| error.to_string())?;
+
+                self.report(&(output.stdout + &output.stderr));
+            }
+            ("encoding", _) => {
+                let mut format = self.format;
+
+                for token in command.argument.split_whitespace() {
+                    match token {
+                        "bom" => format.bom = true,
+                        "nobom" => format.bom = false,
+                        name => {
+                            format.encoding = Encoding::named(name)
+                                .ok_or_else(|| format!("Unknown encoding: {}", name))?
+                        }
+                    };
+                }
+
+                if format.bom && format.encoding.bom().is_empty() {
+                    return Err(format!("{} has no byte order mark", format.encoding.name()));
+                }
+
+                self.format = format;
+
+                match encoding::encode(&self.source, format, self.escaped) {
+                    Ok(_) => self.notify(format!("Encoding: {}", format)),
+                    Err(count) => self.notify(format!(
+                        "Encoding: {} ({} characters cannot be represented)",
+                        format, count
+                    )),
+                };
+            }
+            ("hex", _) => match command.argument.trim() {
+                "" => self.toggle_hex()?,
+                argument => {
+                    let pattern = hex::pattern(argument)
+                        .ok_or_else(|| format!("Invalid byte pattern: {}", argument))?;
+
+                    let found = self.hex.as_mut().ok_or("Not in hex mode")?.find(&pattern);
+
+                    if !found {
+                        return Err(format!("Pattern not found: {}", argument));
+                    }
+                }
+            },
+            ("endings", _) => {
+                let ending = match command.argument.trim() {
+                    "" => self.format.ending,
+                    name => Ending::named(name)
+                        .ok_or_else(|| format!("Unknown line endings: {}", name))?,
+                };
+
+                self.format.ending = ending;
+                self.notify(format!("Line endings: {}", ending.name()));
+            }
+            (name, _) => return Err(format!("Not an editor command: {}", name)),
+        };
+
+        self.operator = None;
+
+        Ok(())
+    }
+
+    fn lines(&self, start: usize, end: usize) -> Range<usize> {
+        let cursor = Motion::Line(start + 1).apply(&self.cursor, &self.source, None);
+
+        Motion::Down.span(&cursor, &self.source, Some(end - start))
+    }
+
+    fn insert_below(&mut self, line: usize, text: &str) {
+        if text.is_empty() {
+            return;
+        }
+
+        let at = line_start(&self.source, line + 1);
+        let unterminated = at == self.source.len() && !self.source.ends_with('\n');
+
+        let (text, first) = match unterminated {
+            true if !self.source.is_empty() => {
+                (format!("\n{}", text.trim_end_matches('\n')), at + 1)
+            }
+            _ if text.ends_with('\n') => (text.to_string(), at),
+            _ => (format!("{}\n", text), at),
+        };
+
+        self.change(at..at, &text);
+        self.cursor = Cursor::default()
+            .seek(&self.source, first)
+            .settle(&self.source);
+    }
+
+    fn report(&mut self, output: &str) {
+        let output = output.trim_end();
+
+        if !output.is_empty() {
+            self.notify(output);
+        }
+    }
+
+    fn motion(&mut self, motion: Motion, count: Option<usize>) -> bool {
+        let operator = self.operator.take();
+
+        let count = match (operator.and_then(|(_, scale)| scale), count) {
+            (Some(scale), Some(count)) => Some(scale * count),
+            (scale, count) => count.or(scale),
+        };
+
+        let target = motion.apply(&self.cursor, &self.source, count);
+
+        if motion.relative() && target.z() == self.cursor.z() {
+            return false;
+        }
+
+        match operator {
+            Some((operator, _)) => {
+                let span = motion.span(&self.cursor, &self.source, count);
+
+                self.operate(operator, vec![span]);
+            }
+            None => self.cursor = target,
+        }
+
+        true
+    }
+
+    fn operator(&mut self, operator: Operator, count: Option<usize>) -> bool {
+        match self.operator.take() {
+            Some((pending, scale)) if pending == operator => {
+                let count = scale.unwrap_or(1) * count.unwrap_or(1);
+
+                let span = Motion::Down.span(&self.cursor, &self.source, Some(count - 1));
+
+                self.operate(operator, vec![span]);
+            }
+            _ => {
+                self.operator = Some((operator, count));
+            }
+        }
+
+        true
+    }
+
+    fn operate(&mut self, operator: Operator, spans: Vec<Range<usize>>) {
+        let start = spans.first().map_or(self.cursor.z(), |span| span.start);
+
+        if matches!(
+            operator,
+            Operator::Delete | Operator::Yank | Operator::Change
+        ) {
+            self.register = spans
+                .iter()
+                .map(|span| &self.source[span.clone()])
+                .join("\n");
+        }
+
+        match operator {
+            Operator::Yank => {}
+            Operator::Delete => {
+                self.edit(
+                    spans
+                        .into_iter()
+                        .map(|span| (span, String::new()))
+                        .collect(),
+                );
+            }
+            Operator::Change => {
+                self.begin();
+
+                let edits = spans
+                    .into_iter()
+                    .map(|span| {
+                        let whole = (span.start == 0 || self.source[..span.start].ends_with('\n'))
+                            && self.source[span.clone()].ends_with('\n');
+
+                        match whole {
+                            true => (span.start..span.end - 1, String::new()),
+                            false => (span, String::new()),
+                        }
+                    })
+                    .collect();
+
+                self.edit(edits);
+                self.cursor = Cursor::default().seek(&self.source, start);
+
+                return;
+            }
+            Operator::ShiftRight | Operator::ShiftLeft => {
+                return self.shift(&spans, 1, operator == Operator::ShiftRight);
+            }
+            Operator::ToggleCase => {
+                let edits = spans
+                    .into_iter()
+                    .map(|span| {
+                        let text = swap_case(&self.source[span.clone()]);
+
+                        (span, text)
+                    })
+                    .collect();
+
+                self.edit(edits);
+            }
+        };
+
+        self.cursor = Cursor::default()
+            .seek(&self.source, start)
+            .settle(&self.source);
+    }
+
+    fn shift(&mut self, spans: &[Range<usize>], levels: usize, right: bool) {
+        let (first, last) = match (spans.first(), spans.last()) {
+            (Some(first), Some(last)) => (first.start, last.end.saturating_sub(1).max(last.start)),
+            _ => return,
+        };
+
+        let line = |z: usize| {
+            self.source[..z.min(self.source.len())]
+                .matches('\n')
+                .count()
+        };
+        let tab_width = self.options.tab_width.max(1);
+        let (unit, width) = self.indentation();
+
+        let edits = (line(first)..=line(last))
+            .filter_map(|y| {
+                let start = line_start(&self.source, y);
+                let text = self.source[start..].split('\n').next().unwrap_or("");
+
+                if text.is_empty() {
+                    return None;
+                }
+
+                if right {
+                    return Some((start..start, unit.repeat(levels)));
+                }
+
+                let mut column = 0;
+
+                let end = text
+                    .char_indices()
+                    .take_while(|(_, character)| {
+                        let fits = column < levels * width;
+
+                        match character {
+                            ' ' => column += 1,
+                            '\t' => column += tab_width - column % tab_width,
+                            _ => return false,
+                        };
+
+                        fits
+                    })
+                    .map(|(index, character)| index + character.len_utf8())
+                    .last()?;
+
+                Some((start..start + end, String::new()))
+            })
+            .collect();
+
+        let y = line(first);
+
+        self.edit(edits);
+
+        let start = line_start(&self.source, y);
+        let text = self.source[start..].split('\n').next().unwrap_or("");
+
+        self.cursor = Cursor::default()
+            .seek(&self.source, start + text.len() - text.trim_start().len())
+            .settle(&self.source);
+    }
+}
+
+impl Buffer {
+    fn indentation(&self) -> (String, usize) {
+        let expand = self
+            .options
+            .expand_tab
+            .or_else(|| indent::detect(&self.source));
+
+        match expand {
+            Some(true) => (
+                " ".repeat(self.options.shift_width),
+                self.options.shift_width,
+            ),
+            _ => ("\t".into(), self.options.tab_width.max(1)),
+        }
+    }
+}
+
+fn per_cursor(action: Action) -> bool {
+    matches!(
+        action,
+        Action::Left
+            | Action::Down
+            | Action::Up
+            | Action::Right
+            | Action::First
+            | Action::Last
+            | Action::Offset
+            | Action::Pair
+            | Action::NextHeading
+            | Action::PreviousHeading
+            | Action::Open
+            | Action::Delete
+            | Action::Yank
+            | Action::Change
+            | Action::ShiftRight
+            | Action::ShiftLeft
+            | Action::ToggleCase
+            | Action::Insert
+            | Action::Append
+            | Action::InsertStart
+            | Action::AppendEnd
+            | Action::OpenBelow
+            | Action::OpenAbove
+            | Action::Replace
+            | Action::ReplaceChar
+            | Action::Newline
+            | Action::Backspace
+            | Action::Remove
+    )
+}
+
+fn adjust(z: usize, edit: &Edit) -> usize {
+    let end = edit.start + edit.removed.len();
+
+    match z {
+        z if z >= end => z - edit.removed.len() + edit.inserted.len(),
+        z if z > edit.start => z.min(edit.start + edit.inserted.len()),
+        z => z,
+    }
+}
+
+fn swap_case(text: &str) -> String {
+    text.chars().fold(
+        String::with_capacity(text.len()),
+        |mut swapped, character| {
+            match character.is_uppercase() {
+                true => swapped.extend(character.to_lowercase()),
+                false => swapped.extend(character.to_uppercase()),
+            };
+
+            swapped
+        },
+    )
+}
+
+use std::ops::Range;
+
+impl Buffer {
+    fn change(&mut self, span: Range<usize>, text: &str) {
+        self.edit(vec![(span, text.into())]);
+    }
+
+    fn edit(&mut self, changes: Vec<(Range<usize>, String)>) {
+        let cursor = changes
+            .first()
+            .map_or(self.cursor.z(), |(span, _)| span.start.min(self.cursor.z()));
+
+        let mut cursors = self.cursors.iter().map(Cursor::z).collect::<Vec<_>>();
+
+        let edits = changes
+            .into_iter()
+            .rev()
+            .map(|(span, text)| {
+                let edit = Edit {
+                    start: span.start,
+                    removed: self.source[span].into(),
+                    inserted: text,
+                };
+
+                edit.apply(&mut self.source);
+                self.follow(&edit);
+
+                for z in cursors.iter_mut() {
+                    *z = adjust(*z, &edit);
+                }
+
+                if let Some(session) = self.session.as_mut() {
+                    for (z, _) in session.replaced.iter_mut() {
+                        *z = adjust(*z, &edit);
+                    }
+                }
+
+                edit
+            })
+            .collect::<Vec<_>>();
+
+        self.touch();
+
+        self.cursors = cursors
+            .into_iter()
+            .map(|z| Cursor::default().seek(&self.source, z))
+            .collect();
+
+        match (self.batch.as_mut(), self.session.as_mut()) {
+            (Some((batch, _)), _) => batch.extend(edits),
+            (None, Some(session)) => session.edits.extend(edits),
+            (None, None) => self.history.record(edits, cursor),
+        };
+    }
+
+    fn touch(&mut self) {
+        if self.options.update_time > 0 {
+            self.changed.get_or_insert_with(Instant::now);
+        }
+    }
+
+    fn follow(&mut self, edit: &Edit) {
+        for mark in self.marks.values_mut() {
+            *mark = adjust(*mark, edit);
+        }
+
+        if let Some((_, anchor, z)) = self.reselect.as_mut() {
+            (*anchor, *z) = (adjust(*anchor, edit), adjust(*z, edit));
+        }
+    }
+
+    fn undo(&mut self) -> bool {
+        match self.history.undo(&mut self.source) {
+            Some((z, edits)) => {
+                for edit in &edits {
+                    self.follow(edit);
+                }
+
+                self.touch();
+                self.cursor = Cursor::default().seek(&self.source, z).settle(&self.source);
+                self.cursors.clear();
+
+                true
+            }
+            None => {
+                self.notify("Already at oldest change");
+
+                false
+            }
         }
     }
 
-    pub fn current(&self) -> &str {
-        UnicodeSegmentation::graphemes(&self.source[..], true)
-            .next()
-            .unwrap_or("")
-    }
+    fn redo(&mut self) -> bool {
+        match self.history.redo(&mut self.source) {
+            Some((z, edits)) => {
+                for edit in &edits {
+                    self.follow(edit);
+                }
+
+                self.touch();
+                self.cursor = Cursor::default().seek(&self.source, z).settle(&self.source);
+                self.cursors.clear();
+
+                true
+            }
+            None => {
+                self.notify("Already at newest change");
 
-    fn scale(&self) -> u32 {
-        self.scale.unwrap_or(1)
+                false
+            }
+        }
     }
 }
 
-use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+#[cfg(test)]
+fn press(buffer: &mut Buffer, keys: &mut keymap::Keymap, input: &str) {
+    use crate::keymap::Step;
+    use crossterm::event::KeyCode;
 
-impl Buffer {
-    pub fn handle(&mut self, event: &Event) {
-        let mut next_scale = None;
-
-        match event {
-            Event::Key(KeyEvent {
-                code: KeyCode::Char('h'),
-                modifiers: _,
-            }) => {
-                self.cursor = self
-                    .cursor
-                    .backward_graphemes(&self.source, self.scale() as usize);
-            }
-            Event::Key(KeyEvent {
-                code: KeyCode::Char('j'),
-                modifiers: _,
-            }) => {
-                self.cursor = self
-                    .cursor
-                    .forward_lines(&self.source, self.scale() as usize);
-            }
-            Event::Key(KeyEvent {
-                code: KeyCode::Char('k'),
-                modifiers: _,
-            }) => {
-                //self.step_backward(Lense::Lines).unwrap();
-            }
-            Event::Key(KeyEvent {
-                code: KeyCode::Char('l'),
-                modifiers: _,
-            }) => {
-                self.cursor = self
-                    .cursor
-                    .forward_graphemes(&self.source, self.scale() as usize);
-            }
-            Event::Key(KeyEvent {
-                code: KeyCode::Char('n'),
-                modifiers: KeyModifiers::CONTROL,
-            }) => {
-                self.mode = match self.mode {
-                    Mode::Graphemes => Mode::Lines,
-                    Mode::Lines => Mode::Graphemes,
-                };
+    for key in keymap::parse(input).unwrap() {
+        keys.push(key);
+    }
+
+    while let Some(step) = keys.next(buffer.input_mode(), true) {
+        match step {
+            Step::Perform {
+                action,
+                count,
+                argument,
+            } => {
+                buffer.perform(action, count, argument);
             }
-            Event::Key(KeyEvent {
-                code: KeyCode::Char(code),
-                modifiers: _,
-            }) => {
-                if let Some(digit) = code.to_digit(10) {
-                    if let Some(previous_scale) = self.scale {
-                        next_scale = Some(previous_scale * 10 + digit);
-                    } else {
-                        next_scale = Some(digit);
-                    }
+            Step::Key(key) => {
+                if let (keymap::Mode::Insert, KeyCode::Char(character)) =
+                    (buffer.input_mode(), key.code)
+                {
+                    buffer.insert(character);
                 }
             }
-            _ => {}
-        };
-
-        self.scale = next_scale;
+            Step::Recursive => {}
+        }
     }
 }
 
+#[test]
+fn operators() {
+    let mut buffer = Buffer::new(include_str!("../edit"));
+    let mut keys = keymap::Keymap::default();
+
+    press(&mut buffer, &mut keys, "3ggdG");
+
+    assert_eq!(buffer.source, "# Jago\n\n");
+    assert_eq!(buffer.register.lines().count(), 9);
+    assert_eq!(buffer.cursor, (0, 1, 7).into());
+
+    press(&mut buffer, &mut keys, "ggyl");
+
+    assert_eq!(buffer.register, "#");
+
+    press(&mut buffer, &mut keys, "gg2dd");
+
+    assert_eq!(buffer.source, "");
+    assert!(buffer.modified());
+
+    press(&mut buffer, &mut keys, "uu");
+
+    assert_eq!(buffer.source, include_str!("../edit"));
+    assert!(!buffer.modified());
+
+    press(&mut buffer, &mut keys, "ggjjjmaG");
+
+    buffer.execute(&ex::parse("'a,.-1d").unwrap()).unwrap();
+
+    assert_eq!(buffer.source.lines().count(), 4);
+    assert_eq!(buffer.cursor, (0, 3, 34).into());
+
+    press(&mut buffer, &mut keys, "kk'a");
+
+    assert_eq!(buffer.cursor, (0, 3, 34).into());
+
+    buffer = Buffer::new("b\nc\na");
+
+    buffer.execute(&ex::parse("%!sort").unwrap()).unwrap();
+
+    assert_eq!(buffer.source, "a\nb\nc\n");
+
+    buffer.execute(&ex::parse("1r !echo x").unwrap()).unwrap();
+
+    assert_eq!(buffer.source, "a\nx\nb\nc\n");
+    assert_eq!(buffer.cursor, (0, 1, 2).into());
+    assert!(buffer.execute(&ex::parse("2,3!exit 1").unwrap()).is_err());
+
+    press(&mut buffer, &mut keys, "uu");
+
+    assert_eq!(buffer.source, "b\nc\na");
+
+    buffer = Buffer::new("fn main() {\n    one();\n}\n");
+
+    press(&mut buffer, &mut keys, "j>>");
+
+    assert_eq!(buffer.source, "fn main() {\n        one();\n}\n");
+
+    press(&mut buffer, &mut keys, "<lt>jA<CR>two();<Esc>");
+
+    assert_eq!(buffer.source, "fn main() {\n    one();\n    two();\n}\n");
+
+    buffer = Buffer::new("> 1. a\n");
+
+    press(&mut buffer, &mut keys, "A<CR>b<CR><CR>c<Esc>o");
+
+    assert_eq!(buffer.source, "> 1. a\n> 2. b\n> c\n> \n");
+}
+
+#[test]
+fn endings() {
+    let mut buffer = Buffer::decoded(b"a\r\nb\r\n".to_vec());
+
+    assert_eq!(buffer.source, "a\nb\n");
+    assert_eq!(buffer.format.ending, Ending::Crlf);
+    assert_eq!(buffer.encoded().unwrap(), &b"a\r\nb\r\n"[..]);
+
+    buffer
+        .execute(&ex::parse("%!printf 'a\\r\\n'").unwrap())
+        .unwrap();
+
+    assert_eq!(buffer.source, "a\n");
+
+    buffer.execute(&ex::parse("endings mac").unwrap()).unwrap();
+
+    assert_eq!(buffer.encoded().unwrap(), &b"a\r"[..]);
+    assert!(buffer.execute(&ex::parse("endings vms").unwrap()).is_err());
+
+    let stray = Buffer::decoded(b"a\rb\nc\n".to_vec());
+
+    assert_eq!(stray.encoded().unwrap(), &b"a\rb\nc\n"[..]);
+}
+
+#[test]
+fn replace() {
+    let mut buffer = Buffer::new("cafe\u{301} au lait\n");
+    let mut keys = keymap::Keymap::default();
+
+    press(&mut buffer, &mut keys, "3lrEgg3rx");
+
+    assert_eq!(buffer.source, "xxxE au lait\n");
+    assert_eq!(buffer.cursor, (2, 0, 2).into());
+    assert!(!buffer.perform(Action::ReplaceChar, Some(20), Some('y')));
+
+    press(&mut buffer, &mut keys, "ggRabcdefghijklmn<BS><BS><BS>");
+
+    assert_eq!(buffer.source, "abcdefghijkt\n");
+
+    press(&mut buffer, &mut keys, "<BS><BS><Esc>");
+
+    assert_eq!(buffer.source, "abcdefghiait\n");
+
+    press(&mut buffer, &mut keys, "u");
+
+    assert_eq!(buffer.source, "xxxE au lait\n");
+
+    press(&mut buffer, &mut keys, "l.");
+
+    assert_eq!(buffer.source, "xabcdefghiit\n");
+}
+
+#[test]
+fn repeat() {
+    let mut buffer = Buffer::new(include_str!("../edit"));
+    let mut keys = keymap::Keymap::default();
+
+    press(&mut buffer, &mut keys, "dj.");
+
+    assert_eq!(buffer.source.lines().count(), 7);
+
+    press(&mut buffer, &mut keys, "3.");
+
+    assert_eq!(buffer.source.lines().count(), 3);
+
+    press(&mut buffer, &mut keys, "uyy.");
+
+    assert_eq!(buffer.source.lines().count(), 3);
+
+    buffer = Buffer::new(include_str!("../edit"));
+
+    press(&mut buffer, &mut keys, "ggoabc<CR>d<Esc>");
+
+    assert!(buffer.source.starts_with("# Jago\nabc\nd\n\n"));
+    assert_eq!(buffer.cursor, (0, 2, 11).into());
+
+    press(&mut buffer, &mut keys, "u");
+
+    assert_eq!(buffer.source, include_str!("../edit"));
+
+    press(&mut buffer, &mut keys, "IX<Esc>jj.");
+
+    assert!(buffer.source.starts_with("X# Jago\n\nX> `Canker`"));
+}
+
+#[test]
+fn selections() {
+    let source = include_str!("../edit");
+    let mut buffer = Buffer::new(source);
+    let mut keys = keymap::Keymap::default();
+
+    press(&mut buffer, &mut keys, "vjd");
+
+    assert!(buffer.source.starts_with("> `Canker`"));
+    assert_eq!(buffer.register, "# Jago\n\n");
+
+    press(&mut buffer, &mut keys, "uVjjy");
+
+    assert_eq!(buffer.register, "# Jago\n\n> `Canker` but communist.\n");
+    assert_eq!(buffer.input_mode(), keymap::Mode::Normal);
+
+    press(&mut buffer, &mut keys, "gv>");
+
+    assert!(buffer.source.starts_with("\t# Jago\n\n\t> `Canker`"));
+
+    press(&mut buffer, &mut keys, "gv3<lt>");
+
+    assert_eq!(buffer.source, source);
+
+    press(&mut buffer, &mut keys, "5gg<C-v>jjl~");
+
+    assert_eq!(buffer.source.lines().nth(6).unwrap().get(..2), Some("tH"));
+
+    press(&mut buffer, &mut keys, "ugvd");
+
+    assert_eq!(buffer.source.lines().nth(4), Some(" Intro"));
+    assert_eq!(
+        buffer.source.lines().nth(6).unwrap().get(..6),
+        Some("e name")
+    );
+    assert_eq!(buffer.register, "##\nTh");
+
+    press(&mut buffer, &mut keys, "uggVj<Esc>");
+
+    buffer.execute(&ex::parse("'<,'>d").unwrap()).unwrap();
+
+    assert!(buffer.source.starts_with("> `Canker`"));
+
+    press(&mut buffer, &mut keys, "uggclX<Esc>jj.");
+
+    assert!(buffer.source.starts_with("X Jago\n\nX `Canker`"));
+
+    press(&mut buffer, &mut keys, "u");
+
+    assert!(buffer.source.starts_with("X Jago\n\n> `Canker`"));
+
+    press(&mut buffer, &mut keys, "u");
+
+    assert_eq!(buffer.source, source);
+
+    press(&mut buffer, &mut keys, "gglvlloy");
+
+    assert_eq!(buffer.register, " Ja");
+    assert_eq!(buffer.cursor, (1, 0, 1).into());
+}
+
+#[test]
+fn marks() {
+    let mut buffer = Buffer::new("aéb\nx\n");
+    let mut keys = keymap::Keymap::default();
+
+    press(&mut buffer, &mut keys, "llmaggdlu");
+
+    assert_eq!(buffer.marks[&'a'], 3);
+
+    press(&mut buffer, &mut keys, "j'a");
+
+    assert_eq!(buffer.cursor.y(), 0);
+
+    press(&mut buffer, &mut keys, "<C-r>");
+
+    assert_eq!(buffer.marks[&'a'], 2);
+}
+
+#[test]
+fn cursors() {
+    let source = include_str!("../edit");
+    let mut buffer = Buffer::new(source);
+    let mut keys = keymap::Keymap::default();
+
+    press(&mut buffer, &mut keys, "3gglll<C-d><C-d>");
+
+    assert_eq!(buffer.cursors.len(), 2);
+    assert_eq!(buffer.cursor, (0, 10, 184).into());
+    assert!(!buffer.perform(Action::AddCursor, None, None));
+
+    press(&mut buffer, &mut keys, "iX<Esc>");
+
+    assert_eq!(buffer.source.matches("XCanker").count(), 3);
+    assert_eq!(buffer.cursor.current(&buffer.source), "X");
+
+    press(&mut buffer, &mut keys, "u");
+
+    assert_eq!(buffer.source, source);
+    assert!(buffer.cursors.is_empty());
+
+    press(&mut buffer, &mut keys, "3gglll<C-d><C-d>l~");
+
+    assert_eq!(buffer.source.matches("CAnker").count(), 3);
+
+    press(&mut buffer, &mut keys, "<C-x>dl");
+
+    assert_eq!(buffer.source.matches("CAker").count(), 2);
+
+    press(&mut buffer, &mut keys, "uu");
+
+    assert_eq!(buffer.source, source);
+
+    press(&mut buffer, &mut keys, "3gglll<C-d><C-k>");
+
+    assert_eq!(buffer.cursors.len(), 1);
+    assert_eq!(buffer.cursor.y(), 10);
+
+    press(&mut buffer, &mut keys, "<Esc>ggVjj<C-l>A;<Esc>");
+
+    assert!(buffer
+        .source
+        .starts_with("# Jago;\n;\n> `Canker` but communist.;\n"));
+    assert_eq!(buffer.cursors.len(), 2);
+
+    press(&mut buffer, &mut keys, "gg");
+
+    assert!(buffer.cursors.is_empty());
+
+    press(&mut buffer, &mut keys, "u");
+
+    assert_eq!(buffer.source, source);
+}
+
+#[test]
+fn swaps() {
+    let directory = std::env::temp_dir().join(format!("edit-swap-{}", std::process::id()));
+    let file = directory.join("notes.md");
+    let swap = swap::path(&file).unwrap();
+
+    let _ = fs::remove_dir_all(&directory);
+    fs::create_dir_all(&directory).unwrap();
+    fs::write(&file, "a\n").unwrap();
+    swap::write(&file, b"b\n").unwrap();
+
+    let mut buffer = Buffer::open(&file, &Config::default().options).unwrap();
+
+    buffer.release();
+
+    assert!(swap.exists());
+
+    buffer.save(None, false).unwrap();
+
+    assert!(swap.exists());
+
+    buffer.recover(b"b\n".to_vec()).unwrap();
+    buffer.save(None, false).unwrap();
+
+    assert!(!swap.exists());
+    assert_eq!(fs::read_to_string(&file).unwrap(), "b\n");
+
+    buffer.options.update_time = 1;
+    buffer.execute(&ex::parse("hex").unwrap()).unwrap();
+    buffer.perform(Action::ReplaceChar, None, Some('4'));
+
+    std::thread::sleep(Duration::from_millis(5));
+    buffer.poll();
+
+    assert_eq!(fs::read(&swap).unwrap(), b"B\n");
+
+    buffer.perform(Action::Undo, None, None);
+    buffer.recover(fs::read(&swap).unwrap()).unwrap();
+    buffer.save(None, false).unwrap();
+
+    assert!(!swap.exists());
+    assert_eq!(fs::read_to_string(&file).unwrap(), "B\n");
+
+    fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn large() {
+    let file = std::env::temp_dir().join(format!("edit-large-{}", std::process::id()));
+
+    fs::write(&file, "a\n".repeat(1 << 19)).unwrap();
+
+    let mut options = Config::default().options;
+
+    options.large_file = (1 << 44) + 1;
+
+    assert!(Buffer::open(&file, &options).unwrap().paged.is_none());
+
+    options.large_file = 0;
+
+    assert!(Buffer::open(&file, &options).unwrap().paged.is_none());
+
+    fs::remove_file(&file).unwrap();
+}
+
+use std::borrow::Cow;
+
 use crossterm::{
     cursor::{MoveTo, MoveToColumn},
-    style::{Color, Print, SetForegroundColor},
-    terminal::{Clear, ClearType},
+    style::{Attribute, Color, Print, SetAttribute, SetForegroundColor},
+    terminal::{size, Clear, ClearType},
     Command,
 };
 use itertools::Itertools;
 
 impl Command for Buffer {
     fn write_ansi(&self, out: &mut impl std::fmt::Write) -> std::fmt::Result {
+        let (columns, rows) = size().unwrap_or((80, 24));
+        let (columns, height) = (columns as usize, (rows as usize).saturating_sub(2).max(1));
+
         Clear(ClearType::All).write_ansi(out)?;
         MoveTo(0, 0).write_ansi(out)?;
 
-        let tokens: Box<dyn Iterator<Item = &str>> = match self.mode {
-            Mode::Graphemes => Box::new(UnicodeSegmentation::graphemes(&self.source[..], true)),
+        let (start, view) = match (&self.hex, &self.paged) {
+            (Some(hex), _) => {
+                SetForegroundColor(self.theme.text.unwrap_or(Color::Reset)).write_ansi(out)?;
+                hex.write_rows(out, height)?;
+
+                (0, Cow::Borrowed(""))
+            }
+            (None, Some(paged)) => (
+                paged.offset(self.top),
+                Cow::Owned(paged.view(self.top, height)),
+            ),
+            (None, None) => {
+                let start = line_start(&self.source, self.top);
+                let end = line_start(&self.source, self.top + height);
+
+                (start, Cow::Borrowed(&self.source[start..end]))
+            }
+        };
+        let view = &view[..];
+
+        let tokens: Box<dyn Iterator<Item = (usize, &str)>> = match self.mode {
+            Mode::Graphemes => Box::new(UnicodeSegmentation::grapheme_indices(view, true)),
             Mode::Lines => Box::new(
-                UnicodeSegmentation::split_word_bound_indices(&self.source[..]).batching(|rest| {
+                UnicodeSegmentation::split_word_bound_indices(view).batching(|rest| {
                     let (mut stop, _) = rest.next()?;
                     let start = stop;
 
-                    while let Some((next_stop, next_word)) = rest.next() {
+                    for (next_stop, next_word) in rest.by_ref() {
                         stop = next_stop;
 
                         if next_word == "\n" {
@@ -129,19 +2463,40 @@ impl Command for Buffer {
                         }
                     }
 
-                    Some(&self.source[start..stop])
+                    Some((start, &view[start..stop]))
                 }),
             ),
         };
 
+        let mut highlighted = match &self.visual {
+            Some(selection) => selection.spans(&self.cursor, &self.source, self.options.tab_width),
+            None => vec![],
+        };
+
+        highlighted.extend(
+            self.cursors
+                .iter()
+                .map(|cursor| cursor.z()..cursor.z() + cursor.current(&self.source).len()),
+        );
+
+        if let Some(pair) = pair::at(&self.source, self.cursor.z()) {
+            highlighted.extend([pair.from(), pair.to()]);
+        }
+
         let mut color_picker = ColorPicker::new();
+        let mut layout = Layout::new(&self.options, columns);
 
         tokens
-            .map(|token| {
-                SetForegroundColor(color_picker.pick()).write_ansi(out)?;
-                Print(token).write_ansi(out)?;
+            .map(|(index, token)| {
+                SetForegroundColor(self.theme.text.unwrap_or_else(|| color_picker.pick()))
+                    .write_ansi(out)?;
+
+                match highlighted.is_empty() {
+                    true => Print(layout.fit(token)).write_ansi(out)?,
+                    false => highlight(out, start + index, token, &highlighted, &mut layout)?,
+                };
 
-                if token == "\n" {
+                if token.ends_with('\n') {
                     MoveToColumn(0).write_ansi(out)?;
                 }
 
@@ -150,22 +2505,237 @@ impl Command for Buffer {
             .find(|result: &Result<(), std::fmt::Error>| result.is_err())
             .unwrap_or(Ok(()))?;
 
-        SetForegroundColor(color_picker.pick()).write_ansi(out)?;
-        MoveToColumn(0).write_ansi(out)?;
+        let message = self.message.as_deref().unwrap_or("");
+        let status = height.saturating_sub(message.lines().count().saturating_sub(1));
+
+        MoveTo(0, status as u16).write_ansi(out)?;
+        Clear(ClearType::FromCursorDown).write_ansi(out)?;
+        SetForegroundColor(self.theme.status.unwrap_or_else(|| color_picker.pick()))
+            .write_ansi(out)?;
+
+        let label = match (&self.session, &self.visual) {
+            _ if self.hex.as_ref().is_some_and(Hex::typing) => " -- HEX REPLACE --",
+            _ if self.hex.is_some() => " -- HEX --",
+            (Some(session), _) if session.replace => " -- REPLACE --",
+            (Some(_), _) => " -- INSERT --",
+            (None, Some(selection)) => match selection.kind {
+                Kind::Charwise => " -- VISUAL --",
+                Kind::Linewise => " -- VISUAL LINE --",
+                Kind::Blockwise => " -- VISUAL BLOCK --",
+            },
+            (None, None) => "",
+        };
+
+        let cursors = match self.cursors.len() {
+            0 => String::new(),
+            count => format!(" [{} cursors]", count + 1),
+        };
+
+        let lines = match &self.paged {
+            Some(paged) if paged.done() => format!(" [{} lines]", paged.lines()),
+            Some(paged) => format!(" [indexing, {} lines]", paged.lines()),
+            None => String::new(),
+        };
+
+        let place = match &self.hex {
+            Some(hex) => format!(
+                "{} {:#x}",
+                hex.current()
+                    .map_or_else(String::new, |byte| format!("{:02x}", byte)),
+                hex.offset()
+            ),
+            None => format!(
+                "{:?} {:?} {}",
+                self.current(),
+                (self.cursor.x(), self.cursor.y()),
+                self.cursor.z()
+            ),
+        };
+
         Print(format!(
-            "\n{:?} {:?} {}",
-            self.current(),
-            (self.cursor.x(), self.cursor.y()),
-            self.cursor.z(),
+            "{}{}{}{}{}{}{}{} {}",
+            self.name,
+            if self.modified() { " [+]" } else { "" },
+            if self.readonly || self.view {
+                " [RO]"
+            } else {
+                ""
+            },
+            match self.format == Format::default() {
+                true => String::new(),
+                false => format!(" [{}]", self.format),
+            },
+            if self.escaped { " [invalid UTF-8]" } else { "" },
+            label,
+            cursors,
+            lines,
+            place,
         ))
         .write_ansi(out)?;
 
-        MoveTo(self.cursor.x() as u16, self.cursor.y() as u16).write_ansi(out)?;
+        for (index, line) in message.lines().enumerate() {
+            if index > 0 {
+                MoveTo(0, (status + index) as u16).write_ansi(out)?;
+            }
+
+            Print(format!(" {}", line)).write_ansi(out)?;
+        }
+
+        let (x, y) = self.position(columns);
+
+        MoveTo(x as u16, y as u16).write_ansi(out)?;
 
         Ok(())
     }
 }
 
+impl Buffer {
+    fn position(&self, columns: usize) -> (usize, usize) {
+        if let Some(hex) = &self.hex {
+            return hex.position();
+        }
+
+        let (above, before) = match &self.paged {
+            Some(paged) => (
+                Cow::Owned(paged.view(self.top, self.cursor.y() - self.top)),
+                "",
+            ),
+            None => {
+                let start = line_start(&self.source, self.top);
+                let line = line_start(&self.source, self.cursor.y());
+
+                (
+                    Cow::Borrowed(&self.source[start..line]),
+                    &self.source[line..self.cursor.z()],
+                )
+            }
+        };
+
+        let mut layout = Layout::new(&self.options, columns);
+
+        layout.fit(before);
+
+        if !self.options.wrap {
+            return (
+                layout.column.min(columns.saturating_sub(1)),
+                self.cursor.y() - self.top,
+            );
+        }
+
+        let above = above
+            .lines()
+            .map(|text| {
+                let mut layout = Layout::new(&self.options, columns);
+
+                layout.fit(text);
+
+                layout.column.max(1).div_ceil(columns.max(1))
+            })
+            .sum::<usize>();
+
+        (
+            layout.column % columns.max(1),
+            above + layout.column / columns.max(1),
+        )
+    }
+}
+
+fn line_start(source: &str, line: usize) -> usize {
+    match line {
+        0 => 0,
+        line => source
+            .match_indices('\n')
+            .nth(line - 1)
+            .map_or(source.len(), |(index, _)| index + 1),
+    }
+}
+
+struct Layout {
+    column: usize,
+    columns: usize,
+    tab_width: usize,
+    wrap: bool,
+}
+
+impl Layout {
+    fn new(options: &Options, columns: usize) -> Self {
+        Self {
+            column: 0,
+            columns,
+            tab_width: options.tab_width.max(1),
+            wrap: options.wrap,
+        }
+    }
+
+    fn fit(&mut self, text: &str) -> String {
+        let mut fitted = String::with_capacity(text.len());
+
+        for grapheme in text.graphemes(true) {
+            let width = match grapheme {
+                "\n" | "\r\n" => {
+                    self.column = 0;
+                    fitted.push_str(grapheme);
+
+                    continue;
+                }
+                grapheme => unicode::width(grapheme, self.column, self.tab_width),
+            };
+
+            if self.wrap || self.column + width <= self.columns {
+                match grapheme {
+                    "\t" => fitted.extend(std::iter::repeat_n(' ', width)),
+                    grapheme => match encoding::escaped(grapheme) {
+                        Some(shown) => fitted.push_str(&shown),
+                        None => fitted.push_str(grapheme),
+                    },
+                };
+            }
+
+            self.column += width;
+        }
+
+        fitted
+    }
+}
+
+fn highlight(
+    out: &mut impl std::fmt::Write,
+    index: usize,
+    token: &str,
+    highlighted: &[Range<usize>],
+    layout: &mut Layout,
+) -> std::fmt::Result {
+    let mut bounds = highlighted
+        .iter()
+        .flat_map(|range| [range.start, range.end])
+        .filter(|bound| index < *bound && *bound < index + token.len())
+        .map(|bound| bound - index)
+        .collect::<Vec<_>>();
+
+    bounds.sort_unstable();
+
+    let mut start = 0;
+
+    for stop in bounds.into_iter().chain([token.len()]) {
+        let piece = &token[start..stop];
+
+        if highlighted
+            .iter()
+            .any(|range| range.contains(&(index + start)))
+        {
+            SetAttribute(Attribute::Reverse).write_ansi(out)?;
+            Print(layout.fit(piece)).write_ansi(out)?;
+            SetAttribute(Attribute::NoReverse).write_ansi(out)?;
+        } else {
+            Print(layout.fit(piece)).write_ansi(out)?;
+        }
+
+        start = stop;
+    }
+
+    Ok(())
+}
+
 use rand::rngs::ThreadRng;
 
 struct ColorPicker {
@@ -177,8 +2747,8 @@ impl ColorPicker {
     fn new() -> Self {
         let mut seq = [0; 231];
 
-        for i in 0..seq.len() {
-            seq[i] = i;
+        for (i, slot) in seq.iter_mut().enumerate() {
+            *slot = i;
         }
 
         Self {
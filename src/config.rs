@@ -0,0 +1,366 @@
+use std::{
+    env, fs, io,
+    path::{Path, PathBuf},
+};
+
+use crossterm::{cursor::CursorShape, event::KeyEvent, style::Color};
+
+use crate::{
+    buffer::Mode,
+    keymap::{self, Action, Target},
+    save::Backup,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Config {
+    pub options: Options,
+    pub theme: Theme,
+    pub bindings: Vec<Binding>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Options {
+    pub tab_width: usize,
+    pub shift_width: usize,
+    pub expand_tab: Option<bool>,
+    pub scroll_off: usize,
+    pub wrap: bool,
+    pub lens: Mode,
+    pub timeout: u64,
+    pub large_file: u64,
+    pub update_time: u64,
+    pub backup: Backup,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Theme {
+    pub cursor: CursorShape,
+    pub text: Option<Color>,
+    pub status: Option<Color>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Binding {
+    pub mode: keymap::Mode,
+    pub keys: Vec<KeyEvent>,
+    pub target: Target,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            options: Options {
+                tab_width: 8,
+                shift_width: 4,
+                expand_tab: None,
+                scroll_off: 0,
+                wrap: true,
+                lens: Mode::Graphemes,
+                timeout: 1000,
+                large_file: 64,
+                update_time: 2000,
+                backup: Backup::Off,
+            },
+            theme: Theme {
+                cursor: CursorShape::UnderScore,
+                text: None,
+                status: None,
+            },
+            bindings: vec![],
+        }
+    }
+}
+
+pub fn directory(variable: &str, fallback: &str) -> Option<PathBuf> {
+    let directory = env::var_os(variable)
+        .filter(|directory| !directory.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(fallback)))?;
+
+    Some(directory.join("edit"))
+}
+
+pub fn path() -> Option<PathBuf> {
+    directory("XDG_CONFIG_HOME", ".config").map(|directory| directory.join("config"))
+}
+
+pub fn load(path: &Path) -> (Config, Vec<Error>) {
+    match fs::read_to_string(path) {
+        Ok(source) => parse(&source),
+        Err(error) if error.kind() == io::ErrorKind::NotFound => (Config::default(), vec![]),
+        Err(error) => (Config::default(), vec![error.into()]),
+    }
+}
+
+pub fn parse(source: &str) -> (Config, Vec<Error>) {
+    let mut config = Config::default();
+    let mut errors = vec![];
+
+    for (index, line) in source.lines().enumerate() {
+        let line = line.trim();
+
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let (directive, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+
+        let result = match directive {
+            "set" => rest
+                .split_whitespace()
+                .try_for_each(|setting| config.set(setting)),
+            "theme" => rest
+                .split_whitespace()
+                .try_for_each(|setting| config.theme(setting)),
+            "map" | "noremap" | "bind" | "vmap" | "vnoremap" | "vbind" | "imap" | "inoremap"
+            | "ibind" | "cmap" | "cnoremap" | "cbind" => config.map(directive, rest.trim()),
+            _ => Err(Invalid::Directive(directive.into())),
+        };
+
+        if let Err(invalid) = result {
+            errors.push(Error::Invalid(index + 1, invalid));
+        }
+    }
+
+    (config, errors)
+}
+
+impl Config {
+    fn set(&mut self, setting: &str) -> Result<(), Invalid> {
+        let (name, value) = match setting.split_once('=') {
+            Some((name, value)) => (name, Some(value)),
+            None => (setting, None),
+        };
+
+        let invalid = || Invalid::Value(name.into(), value.unwrap_or_default().into());
+
+        match (name, value) {
+            ("tabwidth" | "ts", Some(value)) => {
+                self.options.tab_width = value
+                    .parse()
+                    .ok()
+                    .filter(|width| *width > 0)
+                    .ok_or_else(invalid)?;
+            }
+            ("shiftwidth" | "sw", Some(value)) => {
+                self.options.shift_width = value
+                    .parse()
+                    .ok()
+                    .filter(|width| *width > 0)
+                    .ok_or_else(invalid)?;
+            }
+            ("scrolloff" | "so", Some(value)) => {
+                self.options.scroll_off = value.parse().map_err(|_| invalid())?;
+            }
+            ("timeoutlen" | "tm", Some(value)) => {
+                self.options.timeout = value.parse().map_err(|_| invalid())?;
+            }
+            ("largefile" | "lf", Some(value)) => {
+                self.options.large_file = value.parse().map_err(|_| invalid())?;
+            }
+            ("updatetime" | "ut", Some(value)) => {
+                self.options.update_time = value.parse().map_err(|_| invalid())?;
+            }
+            ("wrap", None) => self.options.wrap = true,
+            ("nowrap", None) => self.options.wrap = false,
+            ("expandtab" | "et", None) => self.options.expand_tab = Some(true),
+            ("noexpandtab" | "noet", None) => self.options.expand_tab = Some(false),
+            ("lens", Some("graphemes")) => self.options.lens = Mode::Graphemes,
+            ("lens", Some("lines")) => self.options.lens = Mode::Lines,
+            ("backup" | "bk", Some("off")) => self.options.backup = Backup::Off,
+            ("backup" | "bk", Some("single")) => self.options.backup = Backup::Single,
+            ("backup" | "bk", Some("dated")) => self.options.backup = Backup::Dated,
+            (
+                "tabwidth" | "ts" | "shiftwidth" | "sw" | "scrolloff" | "so" | "timeoutlen" | "tm"
+                | "largefile" | "lf" | "updatetime" | "ut" | "lens" | "backup" | "bk",
+                _,
+            )
+            | ("wrap" | "nowrap" | "expandtab" | "et" | "noexpandtab" | "noet", Some(_)) => {
+                return Err(invalid())
+            }
+            _ => return Err(Invalid::Option(name.into())),
+        };
+
+        Ok(())
+    }
+
+    fn theme(&mut self, setting: &str) -> Result<(), Invalid> {
+        let (name, value) = setting.split_once('=').unwrap_or((setting, ""));
+
+        let invalid = || Invalid::Value(name.into(), value.into());
+
+        match name {
+            "cursor" => {
+                self.theme.cursor = match value {
+                    "block" => CursorShape::Block,
+                    "line" => CursorShape::Line,
+                    "underscore" => CursorShape::UnderScore,
+                    _ => return Err(invalid()),
+                }
+            }
+            "text" => self.theme.text = color(value).ok_or_else(invalid)?,
+            "status" => self.theme.status = color(value).ok_or_else(invalid)?,
+            _ => return Err(Invalid::Option(name.into())),
+        };
+
+        Ok(())
+    }
+
+    fn map(&mut self, directive: &str, rest: &str) -> Result<(), Invalid> {
+        let (notation, right) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+        let right = right.trim();
+
+        if notation.is_empty() || right.is_empty() {
+            return Err(Invalid::Map(rest.into()));
+        }
+
+        let (mode, kind) = match directive.split_at(1) {
+            ("v", kind) => (keymap::Mode::Visual, kind),
+            ("i", kind) => (keymap::Mode::Insert, kind),
+            ("c", kind) => (keymap::Mode::Prompt, kind),
+            _ => (keymap::Mode::Normal, directive),
+        };
+
+        let target = match kind {
+            "bind" => {
+                Target::Action(Action::named(right).ok_or_else(|| Invalid::Action(right.into()))?)
+            }
+            kind => Target::Keys(keymap::parse(right).map_err(Invalid::Key)?, kind == "map"),
+        };
+
+        let binding = Binding {
+            mode,
+            keys: keymap::parse(notation).map_err(Invalid::Key)?,
+            target,
+        };
+
+        self.bindings
+            .retain(|existing| (existing.mode, &existing.keys) != (binding.mode, &binding.keys));
+        self.bindings.push(binding);
+
+        Ok(())
+    }
+}
+
+fn color(value: &str) -> Option<Option<Color>> {
+    match value {
+        "random" => Some(None),
+        "default" => Some(Some(Color::Reset)),
+        value => match value.parse() {
+            Ok(value) => Some(Some(Color::AnsiValue(value))),
+            Err(_) => Color::try_from(value).ok().map(Some),
+        },
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum Invalid {
+    #[error("unknown directive {0}")]
+    Directive(String),
+    #[error("unknown option {0}")]
+    Option(String),
+    #[error("invalid value \"{1}\" for {0}")]
+    Value(String, String),
+    #[error("invalid key {0}")]
+    Key(String),
+    #[error("map needs keys on both sides: {0}")]
+    Map(String),
+    #[error("unknown action {0}")]
+    Action(String),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("line {0}: {1}")]
+    Invalid(usize, Invalid),
+    #[error("Io {0}")]
+    Io(#[from] io::Error),
+}
+
+#[test]
+fn settings() {
+    use crossterm::event::{KeyCode, KeyModifiers};
+
+    let (config, errors) = parse(
+        "# options\n\
+         set tabwidth=4 so=3 nowrap tm=500 sw=2 et lf=0 ut=500\n\
+         set lens=lines bk=dated\n\
+         \n\
+         theme cursor=block text=default status=214\n\
+         map <C-s> :w<CR>\n\
+         noremap gb :bn<CR>\n\
+         cbind <C-a> home\n\
+         ibind <C-h> backspace\n\
+         set tabwidth=0 wrap=1\n\
+         set colour=red\n\
+         theme text=mauve\n\
+         map <Hyper-x> w\n\
+         bind zz frobnicate\n\
+         macro q quit\n",
+    );
+
+    assert_eq!(
+        config.options,
+        Options {
+            tab_width: 4,
+            shift_width: 2,
+            expand_tab: Some(true),
+            scroll_off: 3,
+            wrap: false,
+            lens: Mode::Lines,
+            timeout: 500,
+            large_file: 0,
+            update_time: 500,
+            backup: Backup::Dated,
+        }
+    );
+
+    assert_eq!(
+        config.theme,
+        Theme {
+            cursor: CursorShape::Block,
+            text: Some(Color::Reset),
+            status: Some(Color::AnsiValue(214)),
+        }
+    );
+
+    assert_eq!(
+        config.bindings,
+        [
+            Binding {
+                mode: keymap::Mode::Normal,
+                keys: vec![KeyEvent::new(KeyCode::Char('s'), KeyModifiers::CONTROL)],
+                target: Target::Keys(keymap::parse(":w<CR>").unwrap(), true),
+            },
+            Binding {
+                mode: keymap::Mode::Normal,
+                keys: keymap::parse("gb").unwrap(),
+                target: Target::Keys(keymap::parse(":bn<CR>").unwrap(), false),
+            },
+            Binding {
+                mode: keymap::Mode::Prompt,
+                keys: keymap::parse("<C-a>").unwrap(),
+                target: Target::Action(Action::Home),
+            },
+            Binding {
+                mode: keymap::Mode::Insert,
+                keys: keymap::parse("<C-h>").unwrap(),
+                target: Target::Action(Action::Backspace),
+            },
+        ]
+    );
+
+    let errors = errors.iter().map(ToString::to_string).collect::<Vec<_>>();
+
+    assert_eq!(
+        errors,
+        [
+            "line 10: invalid value \"0\" for tabwidth",
+            "line 11: unknown option colour",
+            "line 12: invalid value \"mauve\" for text",
+            "line 13: invalid key <Hyper-x>",
+            "line 14: unknown action frobnicate",
+            "line 15: unknown directive macro",
+        ]
+    );
+}
@@ -23,14 +23,13 @@ impl From<(usize, usize, usize)> for Cursor {
 
 use unicode_segmentation::UnicodeSegmentation;
 
+use crate::unicode::is_break;
+
 impl Cursor {
     pub fn current<'a>(&self, buffer: &'a str) -> &'a str {
         let mut buffer = UnicodeSegmentation::graphemes(&buffer[self.z()..], true);
 
-        match buffer.next() {
-            Some(next) => next,
-            None => "",
-        }
+        buffer.next().unwrap_or_default()
     }
 }
 
@@ -47,7 +46,7 @@ impl Cursor {
                 next.2 += grapheme.len();
                 next.0 += grapheme.len();
 
-                if "\n" == grapheme {
+                if is_break(grapheme) {
                     next.1 += 1;
                     next.0 = 0;
                 }
@@ -55,8 +54,10 @@ impl Cursor {
                 next
             });
 
-        if next.current(input) == "\n" {
-            next.2 += 1;
+        let current = next.current(input);
+
+        if is_break(current) {
+            next.2 += current.len();
             next.1 += 1;
             next.0 = 0;
         }
@@ -184,33 +185,31 @@ impl Cursor {
     */
 
     pub fn backward_graphemes(&self, input: &str, count: usize) -> Self {
-        let next = input
+        let (next, _) = input
             .get(..self.z())
             .iter()
             .flat_map(|slice| slice.graphemes(true))
             .rev()
-            .fold_while(self.clone(), |mut next, grapheme| {
+            .fold_while((self.clone(), 0), |(mut next, mut steps), grapheme| {
                 next.2 -= grapheme.len();
 
-                if "\n" == grapheme {
+                if is_break(grapheme) {
                     next.1 -= 1;
                 } else {
-                    next.0 -= grapheme.len();
+                    steps += 1;
                 }
 
-                if self.z() - next.z() >= count {
-                    if "\n" == grapheme {
-                        Continue(next)
-                    } else {
-                        Done(next)
-                    }
+                if steps >= count && !is_break(grapheme) {
+                    Done((next, steps))
                 } else {
-                    Continue(next)
+                    Continue((next, steps))
                 }
             })
             .into_inner();
 
-        next
+        let line_start = line_start(&input[..next.z()]);
+
+        Cursor(next.z() - line_start, next.y(), next.z())
 
         /*
         let mut next = input
@@ -303,7 +302,7 @@ fn backward_graphemes() {
         ((3, 0, 3), 2, (1, 0, 1), " "),
         ((0, 1, 7), 3, (3, 0, 3), "a"),
         ((3, 2, 11), 4, (5, 0, 5), "o"),
-        ((8, 2, 15), 5, (3, 2, 10), "C"),
+        ((7, 2, 15), 5, (2, 2, 10), "`"),
     ];
 
     for (from, steps, to, want) in tests {
@@ -327,9 +326,9 @@ impl Cursor {
                 dx = 0;
 
                 match line_bounds.peek() {
-                    Some(&"\n") => {
-                        dz += "\n".len();
-                        drop(line_bounds.next());
+                    Some(token) if is_break(token) => {
+                        dz += token.len();
+                        line_bounds.next();
                     }
                     None => break,
                     _ => {}
@@ -345,13 +344,13 @@ impl Cursor {
 
         for _ in 0..self.x() {
             match graphemes.peek() {
-                Some(&"\n") => {
+                Some(grapheme) if is_break(grapheme) => {
                     break;
                 }
                 Some(grapheme) => {
                     dz += grapheme.len();
                     dx += grapheme.len();
-                    drop(line_bounds.next());
+                    graphemes.next();
                 }
                 None => break,
             };
@@ -397,4 +396,86 @@ fn test_forward_lines() {
     for (from, steps, to, want) in tests {
         assert_!(from, steps, to, want);
     }
+
+    let buffer = "abc\r\nde\rfghi";
+    let from: Cursor = (2, 0, 2).into();
+
+    assert_eq!(from.forward_lines(buffer, 1), (2, 1, 7).into());
+    assert_eq!(from.forward_lines(buffer, 2), (2, 2, 10).into());
+}
+
+impl Cursor {
+    pub fn seek(&self, buffer: &str, z: usize) -> Self {
+        let z = z.min(buffer.len());
+
+        let new_lines = |span: &str| {
+            span.match_indices(['\n', '\r'])
+                .filter(|(index, found)| *found == "\n" || !span[index + 1..].starts_with('\n'))
+                .count()
+        };
+
+        let y = if z >= self.z() {
+            self.y() + new_lines(&buffer[self.z()..z])
+        } else {
+            self.y() - new_lines(&buffer[z..self.z()])
+        };
+
+        let line_start = line_start(&buffer[..z]);
+
+        Cursor(z - line_start, y, z)
+    }
+}
+
+impl Cursor {
+    pub fn settle(&self, buffer: &str) -> Self {
+        let last = buffer.graphemes(true).next_back().unwrap_or_default();
+
+        match self.current(buffer) {
+            grapheme if is_break(grapheme) && self.x() > 0 => self.backward_graphemes(buffer, 1),
+            "" if is_break(last) => {
+                let last_line = line_start(&buffer[..buffer.len() - last.len()]);
+
+                self.seek(buffer, last_line)
+            }
+            "" if self.z() > 0 => self.backward_graphemes(buffer, 1),
+            _ => self.clone(),
+        }
+    }
+}
+
+fn line_start(before: &str) -> usize {
+    before.rfind(['\n', '\r']).map_or(0, |index| index + 1)
+}
+
+#[test]
+fn seek() {
+    let buffer = include_str!("../edit");
+
+    let from = Cursor::default();
+
+    let to = from.seek(buffer, 11);
+
+    assert_eq!(to, (3, 2, 11).into());
+    assert_eq!(to.current(buffer), "C");
+
+    let to = to.seek(buffer, 5);
+
+    assert_eq!(to, (5, 0, 5).into());
+    assert_eq!(to.current(buffer), "o");
+}
+
+#[test]
+fn carriage_returns() {
+    let buffer = "a\rb\r\nc\r";
+
+    let from = Cursor::default();
+
+    assert_eq!(from.forward_graphemes(buffer, 1), (0, 1, 2).into());
+    assert_eq!(from.seek(buffer, 5), (0, 2, 5).into());
+    assert_eq!(
+        from.seek(buffer, 5).backward_graphemes(buffer, 1),
+        (0, 1, 2).into()
+    );
+    assert_eq!(Cursor::from((1, 0, 1)).settle(buffer), (0, 0, 0).into());
+    assert_eq!(Cursor::from((0, 3, 7)).settle(buffer), (0, 2, 5).into());
 }
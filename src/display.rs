@@ -1,70 +1,122 @@
 use std::{
     env::current_dir,
-    fs::File,
-    io::{stdout, Read, Write},
+    fs::OpenOptions,
+    io::{stdin, stdout, IsTerminal, Read, Write},
     iter::Peekable,
+    time::Duration,
 };
 
 use crossterm::{
     cursor::{CursorShape, SetCursorShape},
-    event::{read, Event, KeyCode, KeyEvent},
+    event::{poll, read},
     execute, queue,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
 
-use crate::buffer::Buffer;
+use crate::{buffer::Buffer, config, editor::Editor, prompt, state};
 
-pub fn handle(_: &mut Peekable<impl Iterator<Item = String>>) -> Result<(), Error> {
-    let mut source = vec![];
+pub fn handle(input: &mut Peekable<impl Iterator<Item = String>>) -> Result<(), Error> {
+    let (mut piped, mut pipe, mut readonly, mut files) = (false, false, false, vec![]);
 
-    let target = current_dir().map_err(Error::from).and_then(|directory| {
-        directory
-            .file_stem()
-            .ok_or_else(|| Error::Incomplete)
-            .map(|file_stem| directory.join(file_stem))
-    })?;
+    for argument in input {
+        match argument.as_str() {
+            "-" => piped = true,
+            "--stdout" => pipe = true,
+            "-R" | "--readonly" => readonly = true,
+            flag if flag.starts_with("--") => return Err(Error::Argument(argument)),
+            _ => files.push(argument),
+        }
+    }
 
-    let mut file = File::open(target)?;
+    if pipe && !piped {
+        return Err(Error::Argument("--stdout without -".into()));
+    }
 
-    file.read_to_end(&mut source)?;
+    let mut editor = match piped {
+        true => {
+            let mut bytes = vec![];
 
-    let mut buffer = Buffer::new(unsafe { std::str::from_utf8_unchecked(&source) });
+            stdin().read_to_end(&mut bytes)?;
 
-    let mut output = stdout();
+            let buffer = Buffer::stdin(bytes.clone());
 
-    execute!(
-        output,
-        EnterAlternateScreen,
-        SetCursorShape(CursorShape::UnderScore),
-        &buffer,
-    )?;
+            let mut editor = Editor::new(buffer);
+
+            if pipe {
+                editor.pipe(bytes);
+            }
+
+            editor
+        }
+        false => Editor::new(Buffer::new("")),
+    };
+
+    if readonly {
+        editor.protect();
+    }
+
+    if let Some(file) = prompt::history() {
+        editor.load_history(file);
+    }
+
+    if let Some(file) = state::path() {
+        editor.load_state(file);
+    }
+
+    if let Some(path) = config::path() {
+        editor.load_config(path);
+    }
+
+    let directory = current_dir()?;
+
+    if files.is_empty() && !piped {
+        let target = directory
+            .file_stem()
+            .ok_or(Error::Incomplete)
+            .map(|file_stem| directory.join(file_stem))?;
+
+        editor.open(&target)?;
+    }
+
+    for file in files {
+        editor.open(&directory.join(file))?;
+    }
+
+    let mut output: Box<dyn Write> = match stdout().is_terminal() {
+        true => Box::new(stdout()),
+        false => Box::new(OpenOptions::new().write(true).open("/dev/tty")?),
+    };
+
+    execute!(output, EnterAlternateScreen, &editor)?;
 
     enable_raw_mode()?;
 
+    let mut dirty = true;
+
     loop {
-        disable_raw_mode()?;
+        if dirty {
+            disable_raw_mode()?;
 
-        let position = crossterm::cursor::position()?;
+            execute!(output, &editor)?;
 
-        execute!(output, &buffer)?;
+            enable_raw_mode()?;
+        }
 
-        enable_raw_mode()?;
+        if !poll(Duration::from_millis(50))? {
+            dirty = editor.poll();
 
-        let event = read()?;
+            continue;
+        }
 
-        buffer.handle(&event);
+        dirty = true;
 
-        match event {
-            Event::Key(KeyEvent {
-                code: KeyCode::Char('q'),
-                ..
-            }) => {
-                break;
-            }
-            _ => {}
-        };
+        editor.handle(&read()?);
 
-        queue!(output, &buffer)?;
+        if editor.quitting() {
+            break;
+        }
+
+        queue!(output, &editor)?;
 
         output.flush()?;
     }
@@ -77,6 +129,10 @@ pub fn handle(_: &mut Peekable<impl Iterator<Item = String>>) -> Result<(), Erro
         LeaveAlternateScreen
     )?;
 
+    if let Some(bytes) = editor.output() {
+        stdout().write_all(bytes)?;
+    }
+
     Ok(())
 }
 
@@ -84,10 +140,10 @@ pub fn handle(_: &mut Peekable<impl Iterator<Item = String>>) -> Result<(), Erro
 pub enum Error {
     #[error("Incomplete")]
     Incomplete,
+    #[error("Invalid argument {0}")]
+    Argument(String),
     #[error("Io {0}")]
     Io(#[from] std::io::Error),
     //#[error("Document {0}")]
     //Document(#[from] crate::document::Error),
-    #[error("Buffer")]
-    Buffer,
 }
@@ -1,6 +1,6 @@
 #[derive(Default)]
 pub struct Document<'a> {
-    buffer: &'a [u8],
+    buffer: &'a str,
     lense: Lense,
     position: usize,
     point: (usize, usize),
@@ -13,37 +13,35 @@ pub struct Cursor {
     z: usize,
 }
 
+#[derive(Default)]
 pub enum Lense {
+    #[default]
     Graphemes,
     Words,
     Sentences,
     Lines,
 }
 
-impl Default for Lense {
-    fn default() -> Self {
-        Lense::Graphemes
-    }
-}
-
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
     #[error("Machine {0}")]
     Machine(#[from] std::io::Error),
+    #[error("Utf8 {0}")]
+    Utf8(#[from] std::str::Utf8Error),
 }
 
 impl<'a> Document<'a> {
-    pub fn new(buffer: &'a [u8]) -> Self {
-        Self {
-            buffer,
+    pub fn new(buffer: &'a [u8]) -> Result<Self, Error> {
+        Ok(Self {
+            buffer: std::str::from_utf8(buffer)?,
             lense: Lense::Sentences,
             position: 0,
             point: (0, 0),
-        }
+        })
     }
 
     pub fn current(&self, lense: Lense) -> Option<&'a str> {
-        let buffer = unsafe { std::str::from_utf8_unchecked(&self.buffer[self.position..]) };
+        let buffer = self.buffer.get(self.position..)?;
 
         let mut tokens: Box<dyn Iterator<Item = &str>> = match lense {
             Lense::Graphemes => Box::new(UnicodeSegmentation::graphemes(buffer, true)),
@@ -71,7 +69,7 @@ impl<'a> Command for Document<'a> {
         Clear(ClearType::All).write_ansi(out)?;
         MoveTo(0, 0).write_ansi(out)?;
 
-        let buffer = unsafe { std::str::from_utf8_unchecked(&self.buffer) };
+        let buffer = self.buffer;
 
         let tokens: Box<dyn Iterator<Item = &str>> = match self.lense {
             Lense::Graphemes => Box::new(UnicodeSegmentation::graphemes(buffer, true)),
@@ -124,8 +122,8 @@ impl ColorPicker {
     fn new() -> Self {
         let mut seq = [0; 231];
 
-        for i in 0..seq.len() {
-            seq[i] = i;
+        for (i, slot) in seq.iter_mut().enumerate() {
+            *slot = i;
         }
 
         Self {
@@ -143,39 +141,24 @@ impl ColorPicker {
     }
 }
 
-use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+use crate::keymap::Action;
 
 impl<'a> Document<'a> {
-    pub fn handle(&mut self, event: &Event) {
-        match event {
-            Event::Key(KeyEvent {
-                code: KeyCode::Char('h'),
-                modifiers: _,
-            }) => {
+    pub fn perform(&mut self, action: Action) {
+        match action {
+            Action::Left => {
                 self.step_backward(Lense::Graphemes).unwrap();
             }
-            Event::Key(KeyEvent {
-                code: KeyCode::Char('j'),
-                modifiers: _,
-            }) => {
+            Action::Down => {
                 self.step_forward(Lense::Lines).unwrap();
             }
-            Event::Key(KeyEvent {
-                code: KeyCode::Char('k'),
-                modifiers: _,
-            }) => {
+            Action::Up => {
                 self.step_backward(Lense::Lines).unwrap();
             }
-            Event::Key(KeyEvent {
-                code: KeyCode::Char('l'),
-                modifiers: _,
-            }) => {
+            Action::Right => {
                 self.step_forward(Lense::Graphemes).unwrap();
             }
-            Event::Key(KeyEvent {
-                code: KeyCode::Char('n'),
-                modifiers: KeyModifiers::CONTROL,
-            }) => {
+            Action::Lens => {
                 self.lense = match self.lense {
                     Lense::Graphemes => Lense::Words,
                     Lense::Words => Lense::Sentences,
@@ -192,14 +175,12 @@ impl<'a> Document<'a> {
     }
 
     fn step_forward(&mut self, lense: Lense) -> Result<(), Error> {
-        let buffer = &self.buffer[self.position + 1..];
+        let buffer = self.buffer.get(self.position + 1..).unwrap_or_default();
 
-        if buffer.len() == 0 {
+        if buffer.is_empty() {
             return Ok(());
         }
 
-        let buffer = unsafe { std::str::from_utf8_unchecked(buffer) };
-
         let mut tokens: Box<dyn Iterator<Item = &str>> = match &lense {
             Lense::Graphemes => Box::new(UnicodeSegmentation::graphemes(buffer, true)),
             Lense::Words => Box::new(UnicodeSegmentation::split_word_bounds(buffer)),
@@ -233,14 +214,12 @@ impl<'a> Document<'a> {
     }
 
     fn step_backward(&mut self, lense: Lense) -> Result<(), Error> {
-        let buffer = &self.buffer[..self.position];
+        let buffer = self.buffer.get(..self.position).unwrap_or_default();
 
-        if buffer.len() == 0 {
+        if buffer.is_empty() {
             return Ok(());
         }
 
-        let buffer = unsafe { std::str::from_utf8_unchecked(buffer) };
-
         let mut tokens: Box<dyn Iterator<Item = &str>> = match &lense {
             Lense::Graphemes => Box::new(UnicodeSegmentation::graphemes(buffer, true).rev()),
             Lense::Words => Box::new(UnicodeSegmentation::split_word_bounds(buffer).rev()),
@@ -275,7 +254,7 @@ impl<'a> Document<'a> {
 fn test_stepping() {
     let bytes = include_bytes!("../edit");
 
-    let mut document = Document::new(bytes);
+    let mut document = Document::new(bytes).unwrap();
 
     document.step_forward(Lense::Graphemes).unwrap();
 
@@ -329,6 +308,8 @@ fn test_stepping() {
 
 use std::ops::Range;
 
+use crate::unicode;
+
 struct ULineBounds<'a> {
     span: Range<usize>,
     source: &'a str,
@@ -359,6 +340,18 @@ fn test_unicode_lines() {
     assert_eq!(lines.next(), Some("I'm fine."));
     assert_eq!(lines.next(), Some("\n"));
     assert_eq!(lines.next(), None);
+
+    let buffer = "one\r\ntwo\rthree";
+
+    let lines = ULineBounds {
+        span: 0..buffer.len(),
+        source: buffer,
+    };
+
+    assert_eq!(
+        lines.collect::<Vec<_>>(),
+        ["one", "\r\n", "two", "\r", "three"]
+    );
 }
 
 impl<'a> Iterator for ULineBounds<'a> {
@@ -367,21 +360,19 @@ impl<'a> Iterator for ULineBounds<'a> {
     fn next(&mut self) -> Option<&'a str> {
         let buffer = &self.source[self.span.clone()];
 
-        let mut word_bounds = UnicodeSegmentation::split_word_bound_indices(buffer);
-
-        let index = word_bounds
-            .find(|(_, next)| *next == "\n")
-            .map(|(index, _)| index)
-            .or(Some(self.source.len()))?;
+        if buffer.is_empty() {
+            return None;
+        }
 
-        let next_new_line = self.span.start + index;
+        let mut word_bounds = UnicodeSegmentation::split_word_bound_indices(buffer);
 
-        let range = self.span.start..next_new_line;
+        let (index, token) = word_bounds
+            .find(|(_, next)| unicode::is_break(next))
+            .unwrap_or((buffer.len(), ""));
 
-        let range = if range.is_empty() {
-            range.start..range.end + 1
-        } else {
-            range
+        let range = match index {
+            0 => self.span.start..self.span.start + token.len(),
+            index => self.span.start..self.span.start + index,
         };
 
         self.span.start = range.end;
@@ -406,33 +397,42 @@ fn test_unicode_lines_backward() {
     assert_eq!(lines.next_back(), Some("\n"));
     assert_eq!(lines.next_back(), Some("Hello, world!"));
     assert_eq!(lines.next_back(), None);
+
+    let buffer = "one\r\ntwo\rthree";
+
+    let lines = ULineBounds {
+        span: 0..buffer.len(),
+        source: buffer,
+    };
+
+    assert_eq!(
+        lines.rev().collect::<Vec<_>>(),
+        ["three", "\r", "two", "\r\n", "one"]
+    );
 }
 
 impl<'a> DoubleEndedIterator for ULineBounds<'a> {
     fn next_back(&mut self) -> Option<&'a str> {
         let buffer = &self.source[self.span.clone()];
 
-        let mut word_bounds = UnicodeSegmentation::split_word_bound_indices(buffer);
-
-        let index = word_bounds
-            .rfind(|(_, next)| *next == "\n")
-            .map(|(index, _)| index + 1)
-            .or(Some(0))?;
-
-        let range = index..self.span.end;
-
-        if range.start == 0 && range.end == 0 {
+        if buffer.is_empty() {
             return None;
         }
 
-        let range = if range.is_empty() {
-            range.start - 1..range.end
-        } else {
-            range
+        let mut word_bounds = UnicodeSegmentation::split_word_bound_indices(buffer);
+
+        let (index, token) = word_bounds
+            .rfind(|(_, next)| unicode::is_break(next))
+            .unwrap_or((0, ""));
+
+        let range = match index + token.len() {
+            end if end == buffer.len() && !token.is_empty() => index..end,
+            end => end..buffer.len(),
         };
+        let range = self.span.start + range.start..self.span.start + range.end;
 
         self.span.end = range.start;
 
-        self.source.get(dbg!(range))
+        self.source.get(range)
     }
 }
@@ -25,8 +25,8 @@ impl Default for Perspective {
 pub enum Error {
     #[error("Machine {0}")]
     Machine(#[from] std::io::Error),
-    #[error("String {0}")]
-    String(#[from] std::string::FromUtf8Error),
+    #[error("Utf8 {0}")]
+    Utf8(#[from] std::str::Utf8Error),
     #[error("Incomplete")]
     Incomplete,
 }
@@ -43,9 +43,11 @@ use std::io::Write;
 
 impl<'a> Document<'a> {
     pub fn new(source: &'a [u8]) -> Result<Self, Error> {
+        let source = std::str::from_utf8(source)?;
+
         Ok(Self {
-            source: unsafe { std::str::from_utf8_unchecked(source) },
-            buffer: String::from_utf8(source.into())?,
+            source,
+            buffer: source.into(),
             ..Default::default()
         })
     }
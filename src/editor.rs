@@ -0,0 +1,1211 @@
+use std::{
+    collections::HashMap,
+    env::current_dir,
+    fs, io,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use crate::{
+    buffer::Buffer,
+    config::{self, Config},
+    ex::{self, Completion},
+    keymap::{self, Action, Keymap, Step},
+    picker::{Pick, Picker},
+    prompt::{Input, Prompt},
+    search::Search,
+    state::{self, Entry, State},
+    swap,
+    watch::Watch,
+};
+
+const SEARCH: &str = "[Search]";
+
+pub struct Editor {
+    buffers: Vec<Buffer>,
+    active: usize,
+    alternate: Option<usize>,
+    search: Option<Search>,
+    watch: Option<Watch>,
+    state: State,
+    output: Option<Vec<u8>>,
+    readonly: bool,
+    prompt: Prompt,
+    picker: Option<Picker>,
+    keymap: Keymap,
+    config: Config,
+    source: Option<PathBuf>,
+    registers: HashMap<char, String>,
+    recording: Option<(char, Vec<KeyEvent>)>,
+    replayed: Option<char>,
+    replaying: bool,
+    quitting: bool,
+}
+
+impl Editor {
+    pub fn new(buffer: Buffer) -> Self {
+        Self {
+            buffers: vec![buffer],
+            active: 0,
+            alternate: None,
+            search: None,
+            watch: None,
+            state: State::default(),
+            output: None,
+            readonly: false,
+            prompt: Prompt::default(),
+            picker: None,
+            keymap: Keymap::default(),
+            config: Config::default(),
+            source: None,
+            registers: HashMap::new(),
+            recording: None,
+            replayed: None,
+            replaying: false,
+            quitting: false,
+        }
+    }
+
+    pub fn active(&self) -> &Buffer {
+        &self.buffers[self.active]
+    }
+
+    pub fn active_mut(&mut self) -> &mut Buffer {
+        &mut self.buffers[self.active]
+    }
+
+    pub fn load_history(&mut self, file: PathBuf) {
+        self.prompt = Prompt::load(file);
+    }
+
+    pub fn load_state(&mut self, file: PathBuf) {
+        self.state = State::load(file);
+    }
+
+    pub fn load_config(&mut self, path: PathBuf) {
+        self.source = Some(path);
+
+        if let Err(error) = self.reload(None) {
+            self.active_mut().notify(error);
+        }
+    }
+
+    fn reload(&mut self, path: Option<PathBuf>) -> Result<(), String> {
+        let path = path
+            .or_else(|| self.source.clone())
+            .ok_or("No configuration file")?;
+
+        let (config, errors) = config::load(&path);
+
+        self.configure(config);
+
+        match errors.is_empty() {
+            true => Ok(()),
+            false => Err(errors
+                .iter()
+                .map(|error| format!("{}: {}", path.display(), error))
+                .collect::<Vec<_>>()
+                .join("\n")),
+        }
+    }
+
+    fn configure(&mut self, config: Config) {
+        for buffer in self.buffers.iter_mut() {
+            buffer.configure(&config);
+        }
+
+        self.keymap.configure(
+            config
+                .bindings
+                .iter()
+                .map(|binding| (binding.mode, &binding.keys[..], &binding.target)),
+            Duration::from_millis(config.options.timeout),
+        );
+
+        self.config = config;
+    }
+
+    pub fn quitting(&self) -> bool {
+        self.quitting
+    }
+
+    pub fn pipe(&mut self, input: Vec<u8>) {
+        self.output = Some(input);
+    }
+
+    pub fn output(&self) -> Option<&[u8]> {
+        self.output.as_deref()
+    }
+
+    pub fn protect(&mut self) {
+        self.readonly = true;
+
+        for buffer in self.buffers.iter_mut() {
+            buffer.protect();
+        }
+    }
+
+    pub fn open(&mut self, path: &Path) -> io::Result<()> {
+        let index = match self
+            .buffers
+            .iter()
+            .position(|buffer| buffer.path() == Some(path))
+        {
+            Some(index) => index,
+            None => {
+                let mut buffer = Buffer::open(path, &self.config.options)?;
+
+                self.watch.get_or_insert_with(Watch::new).add(path);
+
+                buffer.configure(&self.config);
+
+                if let Some(entry) = self.state.get(path) {
+                    buffer.restore(entry);
+                }
+
+                if self.readonly {
+                    buffer.protect();
+                }
+
+                match self.active().blank() {
+                    true => {
+                        if let Some(message) = self.active().message() {
+                            let message = match buffer.message() {
+                                Some(own) => format!("{}\n{}", message, own),
+                                None => message.into(),
+                            };
+
+                            buffer.notify(message);
+                        }
+
+                        self.buffers[self.active] = buffer;
+                        self.active
+                    }
+                    false => {
+                        self.buffers.push(buffer);
+                        self.buffers.len() - 1
+                    }
+                }
+            }
+        };
+
+        self.switch(index);
+        self.reveal();
+
+        Ok(())
+    }
+
+    fn adopt(&mut self, mut buffer: Buffer) -> usize {
+        buffer.configure(&self.config);
+
+        self.buffers.push(buffer);
+        self.buffers.len() - 1
+    }
+
+    fn switch(&mut self, index: usize) {
+        if index != self.active {
+            self.alternate = Some(self.active);
+            self.active = index;
+        }
+    }
+
+    fn scratch(&mut self, name: &str, input: String) {
+        let mut buffer = Buffer::scratch(name, input);
+
+        match self.buffers.iter().position(|buffer| buffer.name() == name) {
+            Some(index) => {
+                buffer.configure(&self.config);
+
+                self.buffers[index] = buffer;
+                self.switch(index);
+            }
+            None => {
+                let index = self.adopt(buffer);
+
+                self.switch(index);
+            }
+        }
+    }
+
+    fn find(&self, name: &str) -> Result<usize, String> {
+        match name {
+            "" | "%" => return Ok(self.active),
+            "#" => return self.alternate.ok_or_else(|| "No alternate file".into()),
+            _ => {}
+        };
+
+        if let Ok(number) = name.parse::<usize>() {
+            return match number {
+                1.. if number <= self.buffers.len() => Ok(number - 1),
+                _ => Err(format!("Buffer {} does not exist", number)),
+            };
+        }
+
+        if let Some(index) = self.buffers.iter().position(|buffer| buffer.name() == name) {
+            return Ok(index);
+        }
+
+        let mut matches = self
+            .buffers
+            .iter()
+            .enumerate()
+            .filter(|(_, buffer)| buffer.name().contains(name))
+            .map(|(index, _)| index);
+
+        match (matches.next(), matches.next()) {
+            (Some(index), None) => Ok(index),
+            (Some(_), Some(_)) => Err(format!("More than one match for {}", name)),
+            (None, _) => Err(format!("No matching buffer for {}", name)),
+        }
+    }
+
+    fn close(&mut self, index: usize, force: bool) -> Result<(), String> {
+        if self.buffers[index].modified() && !force {
+            return Err(format!(
+                "No write since last change for buffer {} (add ! to override)",
+                index + 1
+            ));
+        }
+
+        let mut buffer = self.buffers.remove(index);
+
+        self.state.remember(buffer.state());
+        buffer.release();
+
+        if self.buffers.is_empty() {
+            self.adopt(Buffer::new(""));
+        }
+
+        let shift = |other: usize| if other > index { other - 1 } else { other };
+
+        self.alternate = self.alternate.filter(|other| *other != index).map(shift);
+
+        if self.active == index {
+            self.active = self
+                .alternate
+                .take()
+                .unwrap_or_else(|| index.min(self.buffers.len() - 1));
+        } else {
+            self.active = shift(self.active);
+        }
+
+        Ok(())
+    }
+
+    fn unsaved(&mut self) -> Result<(), String> {
+        match self.buffers.iter().position(Buffer::modified) {
+            Some(index) => {
+                self.switch(index);
+
+                Err(format!(
+                    "No write since last change for buffer \"{}\" (add ! to override)",
+                    self.active().name()
+                ))
+            }
+            None => Ok(()),
+        }
+    }
+
+    fn save_session(&mut self, name: &str) -> Result<usize, String> {
+        let file = state::session(name)?;
+
+        let entries = self
+            .buffers
+            .iter()
+            .enumerate()
+            .filter_map(|(index, buffer)| Some((index, buffer.state()?)))
+            .collect::<Vec<_>>();
+
+        if entries.is_empty() {
+            return Err("No files to save in a session".into());
+        }
+
+        let listing = entries
+            .iter()
+            .map(|(index, (path, entry))| {
+                let flag = if *index == self.active {
+                    '%'
+                } else if Some(*index) == self.alternate {
+                    '#'
+                } else {
+                    '-'
+                };
+
+                format!("{}\t{}\t{}\n", flag, entry.offset, path.display())
+            })
+            .collect::<String>();
+
+        state::write(&file, &listing).map_err(|error| error.to_string())?;
+
+        self.state
+            .remember(entries.iter().map(|(_, state)| state.clone()));
+
+        Ok(entries.len())
+    }
+
+    fn load_session(&mut self, name: &str) -> Result<(), String> {
+        let file = state::session(name)?;
+
+        let listing =
+            fs::read_to_string(&file).map_err(|_| format!("No session named \"{}\"", name))?;
+
+        let mut buffers = vec![];
+        let (mut active, mut alternate) = (0, None);
+
+        for line in listing.lines() {
+            let mut fields = line.splitn(3, '\t');
+
+            let (flag, offset, path) = match (
+                fields.next(),
+                fields.next().and_then(|offset| offset.parse().ok()),
+                fields.next(),
+            ) {
+                (Some(flag), Some(offset), Some(path)) => (flag, offset, Path::new(path)),
+                _ => continue,
+            };
+
+            let mut buffer = match Buffer::open(path, &self.config.options) {
+                Ok(buffer) => buffer,
+                Err(_) => continue,
+            };
+
+            buffer.configure(&self.config);
+            buffer.restore(&Entry {
+                offset,
+                ..self.state.get(path).cloned().unwrap_or(Entry {
+                    offset,
+                    lens: self.config.options.lens,
+                    marks: vec![],
+                })
+            });
+
+            if self.readonly {
+                buffer.protect();
+            }
+
+            match flag {
+                "%" => active = buffers.len(),
+                "#" => alternate = Some(buffers.len()),
+                _ => {}
+            }
+
+            buffers.push(buffer);
+        }
+
+        if buffers.is_empty() {
+            return Err(format!("No files left in session \"{}\"", name));
+        }
+
+        self.state
+            .remember(self.buffers.iter().filter_map(Buffer::state));
+
+        for buffer in self.buffers.iter_mut() {
+            buffer.release();
+        }
+
+        let watch = self.watch.get_or_insert_with(Watch::new);
+
+        for path in buffers.iter().filter_map(Buffer::path) {
+            watch.add(path);
+        }
+
+        self.buffers = buffers;
+        self.active = active;
+        self.alternate = alternate.filter(|alternate| *alternate != active);
+        self.reveal();
+
+        Ok(())
+    }
+
+    fn listing(&self) -> String {
+        self.buffers
+            .iter()
+            .enumerate()
+            .map(|(index, buffer)| {
+                format!(
+                    "{:>3} {}{} \"{}\" line {}\n",
+                    index + 1,
+                    if index == self.active {
+                        "%a"
+                    } else if Some(index) == self.alternate {
+                        "# "
+                    } else {
+                        "  "
+                    },
+                    if buffer.modified() { " +" } else { "  " },
+                    buffer.name(),
+                    buffer.cursor().y() + 1
+                )
+            })
+            .collect()
+    }
+
+    pub fn poll(&mut self) -> bool {
+        let expired = self.keymap.expired();
+
+        if expired {
+            self.drain(true);
+        }
+
+        let changed = self.watch.as_mut().map(Watch::poll).unwrap_or_default();
+
+        let mut updated = false;
+
+        for buffer in self.buffers.iter_mut() {
+            updated |= buffer.poll();
+
+            if buffer.path().is_some_and(|path| changed.contains(path)) {
+                updated |= buffer.refresh();
+            }
+        }
+
+        let listed = self.picker.as_mut().is_some_and(Picker::poll);
+
+        self.gather() || listed || expired || updated
+    }
+
+    fn gather(&mut self) -> bool {
+        let search = match self.search.as_mut() {
+            Some(search) => search,
+            None => return false,
+        };
+
+        let from = search.len();
+        let searching = !search.done();
+        let found = search.poll();
+
+        let listing = match self
+            .buffers
+            .iter_mut()
+            .find(|buffer| buffer.name() == SEARCH)
+        {
+            Some(listing) => listing,
+            None => return false,
+        };
+
+        if found > 0 {
+            listing.append(&search.listing(from));
+        }
+
+        if searching && search.done() {
+            listing.notify(format!("{} matches", search.len()));
+        }
+
+        found > 0 || (searching && search.done())
+    }
+
+    fn visit(&mut self, index: usize) -> Result<(), String> {
+        let found = match self.search.as_mut().map(|search| search.select(index)) {
+            Some(Some(found)) => found.clone(),
+            Some(None) => return Err("No more items".into()),
+            None => return Err("No search results".into()),
+        };
+
+        self.open(&found.path).map_err(|error| error.to_string())?;
+        self.active_mut().goto(found.offset);
+
+        Ok(())
+    }
+}
+
+use crossterm::{
+    event::{Event, KeyCode, KeyEvent, KeyModifiers},
+    terminal::size,
+};
+
+impl Editor {
+    pub fn handle(&mut self, event: &Event) {
+        if let (Some((_, keys)), Event::Key(key)) = (self.recording.as_mut(), event) {
+            keys.push(*key);
+        }
+
+        if let Some(picker) = self.picker.as_mut() {
+            match picker.handle(event) {
+                Pick::Open(path) => {
+                    self.picker = None;
+
+                    if let Err(error) = self.open(&path) {
+                        self.active_mut().notify(error.to_string());
+                    }
+                }
+                Pick::Cancel => self.picker = None,
+                Pick::Pending => {}
+            };
+
+            return;
+        }
+
+        if let Event::Key(key) = event {
+            self.keymap.push(*key);
+            self.drain(false);
+        }
+    }
+
+    fn drain(&mut self, expired: bool) {
+        loop {
+            let mode = self.mode();
+
+            let step = match self.keymap.next(mode, expired) {
+                Some(step) => step,
+                None => break,
+            };
+
+            if let Err(error) = self.perform(mode, step) {
+                self.abort();
+                self.active_mut().notify(error);
+            }
+        }
+
+        self.replaying = false;
+
+        self.reveal();
+    }
+
+    fn reveal(&mut self) {
+        let (_, rows) = size().unwrap_or((80, 24));
+
+        self.active_mut().scroll(rows as usize);
+    }
+
+    fn mode(&self) -> keymap::Mode {
+        match self.prompt.is_open() {
+            true => keymap::Mode::Prompt,
+            false => self.active().input_mode(),
+        }
+    }
+
+    fn perform(&mut self, mode: keymap::Mode, step: Step) -> Result<(), String> {
+        let (action, count, argument) = match step {
+            Step::Perform {
+                action,
+                count,
+                argument,
+            } => (action, count, argument),
+            Step::Key(key) => {
+                let plain = !key
+                    .modifiers
+                    .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT);
+
+                match (mode, key.code, plain) {
+                    (keymap::Mode::Prompt, KeyCode::Char(character), true) => {
+                        self.prompt.insert(character)
+                    }
+                    (keymap::Mode::Insert, KeyCode::Char(character), true) => {
+                        self.active_mut().insert(character)
+                    }
+                    (keymap::Mode::Insert, KeyCode::Tab, _) => self.active_mut().insert('\t'),
+                    _ => {}
+                };
+
+                return Ok(());
+            }
+            Step::Recursive => return Err("Recursive mapping".into()),
+        };
+
+        if mode == keymap::Mode::Prompt {
+            return match self.prompt.perform(action) {
+                Input::Submit(input) => {
+                    let result = self.execute(&input);
+
+                    self.active_mut().perform(Action::Cancel, None, None);
+
+                    result
+                }
+                Input::Complete => {
+                    self.complete();
+
+                    Ok(())
+                }
+                Input::Cancel => {
+                    self.active_mut().perform(Action::Cancel, None, None);
+
+                    Ok(())
+                }
+                Input::Pending => Ok(()),
+            };
+        }
+
+        match action {
+            Action::Quit => self.execute("q")?,
+            Action::Alternate => self.execute("b#")?,
+            Action::Prompt => {
+                self.active_mut().perform(action, count, argument);
+
+                self.prompt.open(match (mode, count) {
+                    (keymap::Mode::Visual, _) => "'<,'>".into(),
+                    (_, Some(count)) if count > 1 => format!(".,.+{}", count - 1),
+                    (_, Some(_)) => ".".into(),
+                    (_, None) => String::new(),
+                });
+            }
+            Action::Picker => {
+                let root = current_dir().map_err(|error| error.to_string())?;
+                let size = size().map_err(|error| error.to_string())?;
+
+                self.picker = Some(Picker::new(&root, size));
+            }
+            Action::Open if self.active().name() == SEARCH => {
+                let index = self.active().cursor().y();
+
+                self.visit(index)?;
+            }
+            Action::Record => self.record(argument)?,
+            Action::Replay => self.replay(argument, count)?,
+            action => {
+                if !self.active_mut().perform(action, count, argument) {
+                    self.abort();
+                }
+            }
+        };
+
+        Ok(())
+    }
+
+    fn record(&mut self, argument: Option<char>) -> Result<(), String> {
+        if let Some((register, mut keys)) = self.recording.take() {
+            keys.pop();
+
+            self.store(register, &keymap::notation(&keys));
+            self.keymap.record(false);
+
+            return Ok(());
+        }
+
+        match argument {
+            Some(register) if register.is_ascii_alphanumeric() => {
+                self.recording = Some((register, vec![]));
+                self.keymap.record(true);
+
+                Ok(())
+            }
+            Some(register) => Err(format!("Invalid register: {}", register)),
+            None => Ok(()),
+        }
+    }
+
+    fn replay(&mut self, argument: Option<char>, count: Option<usize>) -> Result<(), String> {
+        let register = match argument {
+            Some('@') => self.replayed.ok_or("No previously used register")?,
+            Some(register) => register.to_ascii_lowercase(),
+            None => return Ok(()),
+        };
+
+        let keys = self
+            .registers
+            .get(&register)
+            .ok_or_else(|| format!("Register {} is empty", register))?;
+
+        let keys = keymap::parse(keys).map_err(|key| format!("Invalid key {}", key))?;
+
+        self.replayed = Some(register);
+        self.replaying = true;
+        self.keymap.inject(&keys.repeat(count.unwrap_or(1)));
+
+        Ok(())
+    }
+
+    fn store(&mut self, register: char, keys: &str) {
+        let entry = self
+            .registers
+            .entry(register.to_ascii_lowercase())
+            .or_default();
+
+        if !register.is_ascii_uppercase() {
+            entry.clear();
+        }
+
+        entry.push_str(keys);
+    }
+
+    fn abort(&mut self) {
+        if self.replaying {
+            self.keymap.reset();
+            self.replaying = false;
+        }
+    }
+
+    fn complete(&mut self) {
+        let before = self.prompt.before();
+        let (start, completion) = ex::completion(before);
+        let word = &before[start..];
+
+        let mut candidates = match completion {
+            Completion::Command => ex::names(word),
+            Completion::Buffer => self
+                .buffers
+                .iter()
+                .map(|buffer| buffer.name().to_string())
+                .filter(|name| name.contains(word))
+                .collect(),
+            Completion::Path => paths(word),
+            Completion::Nothing => vec![],
+        };
+
+        candidates.sort();
+
+        self.prompt.complete(start, candidates);
+    }
+
+    pub fn execute(&mut self, input: &str) -> Result<(), String> {
+        let command = ex::parse(input).map_err(|error| error.to_string())?;
+
+        let (name, force, argument) = (command.name, command.force, command.argument.as_str());
+
+        let pipe = name == "write" && argument.starts_with('!');
+
+        if command.range.is_some()
+            && !pipe
+            && !matches!(name, "" | "delete" | "yank" | "!" | "read")
+        {
+            return Err("No range allowed".into());
+        }
+
+        if !matches!(name, "" | "goto" | "delete" | "yank") {
+            self.active_mut().perform(Action::Cancel, None, None);
+        }
+
+        match name {
+            "quit" => {
+                if !force {
+                    self.unsaved()?;
+                }
+
+                self.state
+                    .remember(self.buffers.iter().filter_map(Buffer::state));
+
+                for buffer in self.buffers.iter_mut() {
+                    buffer.release();
+                }
+
+                self.quitting = true;
+            }
+            "write" if pipe => return self.active_mut().execute(&command),
+            "write" | "wq" => {
+                let path = match argument {
+                    "" => None,
+                    argument => Some(
+                        current_dir()
+                            .map_err(|error| error.to_string())?
+                            .join(argument),
+                    ),
+                };
+
+                let staged = path.is_none()
+                    && self.output.is_some()
+                    && self.active().piped()
+                    && self.active().path().is_none();
+
+                let written = match staged {
+                    true => {
+                        let bytes = self
+                            .active_mut()
+                            .stage()
+                            .map_err(|error| error.to_string())?;
+
+                        let written = bytes.len();
+
+                        self.output = Some(bytes);
+
+                        written
+                    }
+                    false => self
+                        .active_mut()
+                        .save(path.as_deref(), force)
+                        .map_err(|error| error.to_string())?,
+                };
+
+                let message = match staged {
+                    true => format!(
+                        "\"{}\" {}B written to stdout on exit",
+                        self.active().name(),
+                        written
+                    ),
+                    false => format!("\"{}\" {}B written", self.active().name(), written),
+                };
+
+                self.active_mut().notify(message);
+
+                if name == "wq" {
+                    return self.execute("q");
+                }
+            }
+            "edit" => match argument {
+                "" => return Err("No file name".into()),
+                argument => {
+                    let path = current_dir()
+                        .map_err(|error| error.to_string())?
+                        .join(argument);
+
+                    self.open(&path).map_err(|error| error.to_string())?;
+                }
+            },
+            "ls" | "buffers" | "files" => {
+                let listing = self.listing();
+
+                self.active_mut().notify(listing);
+            }
+            "buffer" => {
+                let index = self.find(argument)?;
+
+                self.switch(index);
+            }
+            "bnext" => {
+                let index = (self.active + 1) % self.buffers.len();
+
+                self.switch(index);
+            }
+            "bprevious" | "bNext" => {
+                let index = (self.active + self.buffers.len() - 1) % self.buffers.len();
+
+                self.switch(index);
+            }
+            "bdelete" => {
+                let index = self.find(argument)?;
+
+                self.close(index, force)?;
+            }
+            "recover" => {
+                let path = self.active().path().ok_or("No file name")?.to_path_buf();
+
+                let bytes = swap::read(&path)
+                    .map_err(|error| error.to_string())?
+                    .ok_or("No swap file")?;
+
+                match argument {
+                    "" => self.active_mut().recover(bytes)?,
+                    "diff" => {
+                        let text = String::from_utf8_lossy(&bytes);
+                        let diff = swap::diff(self.active().source(), &text);
+
+                        self.scratch("[Swap]", diff);
+                    }
+                    "delete" => {
+                        swap::remove(&path).map_err(|error| error.to_string())?;
+
+                        self.active_mut().notify("Swap file deleted");
+                    }
+                    argument => return Err(format!("Invalid argument: {}", argument)),
+                }
+            }
+            "reload" => match argument {
+                "" => self
+                    .active_mut()
+                    .reload()
+                    .map_err(|error| error.to_string())?,
+                "keep" => self
+                    .active_mut()
+                    .keep()
+                    .map_err(|error| error.to_string())?,
+                "diff" => {
+                    let path = self.active().path().ok_or("No file name")?;
+
+                    let disk = Buffer::open(path, &self.config.options)
+                        .map_err(|error| error.to_string())?;
+
+                    let diff = swap::diff(self.active().source(), disk.source());
+
+                    self.scratch("[Changes]", diff);
+                }
+                argument => return Err(format!("Invalid argument: {}", argument)),
+            },
+            "mksession" => {
+                let count = self.save_session(argument)?;
+
+                self.active_mut()
+                    .notify(format!("Session \"{}\" saved, {} files", argument, count));
+            }
+            "session" => {
+                if !force {
+                    self.unsaved()?;
+                }
+
+                self.load_session(argument)?;
+            }
+            "grep" => {
+                let root = current_dir().map_err(|error| error.to_string())?;
+
+                let search = Search::new(&root, argument).map_err(|error| error.to_string())?;
+
+                self.search = Some(search);
+                self.scratch(SEARCH, String::new());
+            }
+            "cnext" => {
+                let index = self.search.as_ref().map_or(0, Search::next);
+
+                self.visit(index)?;
+            }
+            "cprevious" => {
+                let index = self
+                    .search
+                    .as_ref()
+                    .and_then(Search::previous)
+                    .ok_or("No previous items")?;
+
+                self.visit(index)?;
+            }
+            "source" => {
+                let path = match argument {
+                    "" => None,
+                    argument => Some(
+                        current_dir()
+                            .map_err(|error| error.to_string())?
+                            .join(argument),
+                    ),
+                };
+
+                self.reload(path)?;
+
+                self.active_mut().notify("Configuration reloaded");
+            }
+            "registers" | "display" => {
+                let mut registers = self.registers.iter().collect::<Vec<_>>();
+
+                registers.sort();
+
+                let listing = registers
+                    .into_iter()
+                    .map(|(register, keys)| format!("\"{}   {}\n", register, keys))
+                    .collect::<String>();
+
+                self.active_mut()
+                    .notify(format!("--- Registers ---\n{}", listing));
+            }
+            "let" => {
+                let (register, keys) = argument
+                    .strip_prefix('@')
+                    .and_then(|rest| {
+                        let mut characters = rest.chars();
+                        let register = characters.next()?;
+                        let keys = characters.as_str().trim_start().strip_prefix('=')?;
+
+                        Some((register, keys.trim()))
+                    })
+                    .ok_or_else(|| format!("Invalid expression: {}", argument))?;
+
+                if !register.is_ascii_alphanumeric() {
+                    return Err(format!("Invalid register: {}", register));
+                }
+
+                let keys = match (keys.chars().next(), keys.chars().next_back()) {
+                    (Some(open @ ('\'' | '"')), Some(close)) if open == close && keys.len() > 1 => {
+                        &keys[1..keys.len() - 1]
+                    }
+                    _ => keys,
+                };
+
+                keymap::parse(keys).map_err(|key| format!("Invalid key {}", key))?;
+
+                self.store(register, keys);
+            }
+            "copen" => {
+                let listing = match &self.search {
+                    Some(search) => search.listing(0),
+                    None => return Err("No search results".into()),
+                };
+
+                self.scratch(SEARCH, listing);
+            }
+            _ => return self.active_mut().execute(&command),
+        };
+
+        Ok(())
+    }
+}
+
+use crossterm::{
+    cursor::{CursorShape, MoveTo, RestorePosition, SavePosition, SetCursorShape},
+    style::Print,
+    Command,
+};
+
+impl Command for Editor {
+    fn write_ansi(&self, out: &mut impl std::fmt::Write) -> std::fmt::Result {
+        let shape = match self.mode() {
+            keymap::Mode::Insert => CursorShape::Line,
+            _ => self.config.theme.cursor,
+        };
+
+        SetCursorShape(shape).write_ansi(out)?;
+
+        if let Some(picker) = &self.picker {
+            return picker.write_ansi(out);
+        }
+
+        self.active().write_ansi(out)?;
+        self.prompt.write_ansi(out)?;
+
+        match &self.recording {
+            Some((register, _)) if !self.prompt.is_open() => {
+                let (_, rows) = size().unwrap_or((80, 24));
+
+                SavePosition.write_ansi(out)?;
+                MoveTo(0, rows.saturating_sub(1)).write_ansi(out)?;
+                Print(format!("recording @{}", register)).write_ansi(out)?;
+                RestorePosition.write_ansi(out)
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+fn paths(word: &str) -> Vec<String> {
+    let (directory, file) = match word.rfind('/') {
+        Some(index) => word.split_at(index + 1),
+        None => ("", word),
+    };
+
+    let entries = match fs::read_dir(if directory.is_empty() { "." } else { directory }) {
+        Ok(entries) => entries,
+        Err(_) => return vec![],
+    };
+
+    entries
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let name = entry.file_name().into_string().ok()?;
+
+            if !name.starts_with(file) || (name.starts_with('.') && !file.starts_with('.')) {
+                return None;
+            }
+
+            let suffix = match entry.file_type().ok()?.is_dir() {
+                true => "/",
+                false => "",
+            };
+
+            Some(format!("{}{}{}", directory, name, suffix))
+        })
+        .collect()
+}
+
+#[test]
+fn buffers() {
+    let mut editor = Editor::new(Buffer::scratch("one", include_str!("../edit")));
+
+    macro_rules! keys {
+        ($keys:expr) => {
+            for key in keymap::parse($keys).unwrap() {
+                editor.handle(&Event::Key(key));
+            }
+        };
+    }
+
+    editor.buffers.push(Buffer::scratch("two", ""));
+    editor.buffers.push(Buffer::scratch("three", ""));
+
+    editor.execute("b two").unwrap();
+    assert_eq!(editor.active().name(), "two");
+
+    editor.execute("bn").unwrap();
+    assert_eq!(editor.active().name(), "three");
+
+    editor.execute("b#").unwrap();
+    assert_eq!(editor.active().name(), "two");
+
+    editor.execute("bp").unwrap();
+    assert_eq!(editor.active().name(), "one");
+
+    assert!(editor.execute("b o").is_err());
+    assert!(editor.execute("b 4").is_err());
+
+    editor.prompt.open("b t");
+    editor.complete();
+    assert_eq!(editor.prompt.input(), "b three");
+
+    keys!("<Esc>");
+    assert!(!editor.prompt.is_open());
+
+    keys!("2:");
+    assert_eq!(editor.prompt.input(), ".,.+1");
+
+    keys!("<Esc>:2,3bn<CR>");
+    assert_eq!(editor.active().name(), "one");
+
+    keys!("dd");
+    assert_eq!(editor.active().current(), "\n");
+
+    keys!("d:3<CR>");
+    assert_eq!(editor.active().current(), "#");
+
+    assert!(editor
+        .listing()
+        .starts_with("  1 %a + \"one\" line 1\n  2 #    \"two\""));
+
+    assert!(editor.execute("bd").is_err());
+
+    keys!("<C-^>:q<CR>");
+    assert_eq!(editor.active().name(), "one");
+    assert!(!editor.quitting());
+
+    editor.execute("bd!").unwrap();
+    assert_eq!(editor.active().name(), "two");
+    assert_eq!(editor.alternate, None);
+
+    editor.execute("bd 2").unwrap();
+    assert_eq!(editor.active().name(), "two");
+
+    editor.buffers.push(Buffer::scratch("four", ""));
+    editor.configure(config::parse("set lens=lines\nnoremap gb :bp<CR>\nbind Q quit").0);
+
+    keys!("gb");
+    assert_eq!(editor.active().name(), "four");
+
+    keys!("<C-^>Q");
+    assert_eq!(editor.active().name(), "two");
+    assert!(editor.quitting());
+}
+
+#[test]
+fn pipeline() {
+    let mut editor = Editor::new(Buffer::stdin(b"one\r\ntwo\r\n".to_vec()));
+
+    editor.pipe(b"one\r\ntwo\r\n".to_vec());
+
+    for key in keymap::parse("dd").unwrap() {
+        editor.handle(&Event::Key(key));
+    }
+
+    assert!(editor.execute("q").is_err());
+
+    editor.execute("wq").unwrap();
+    assert_eq!(editor.output(), Some(&b"two\r\n"[..]));
+    assert!(editor.quitting());
+}
+
+#[test]
+fn macros() {
+    let mut editor = Editor::new(Buffer::scratch("one", include_str!("../edit")));
+
+    macro_rules! keys {
+        ($keys:expr) => {
+            for key in keymap::parse($keys).unwrap() {
+                editor.handle(&Event::Key(key));
+            }
+        };
+    }
+
+    keys!("qajjq");
+    assert_eq!(editor.registers[&'a'], "jj");
+    assert_eq!(editor.active().cursor().y(), 2);
+
+    keys!("2@a");
+    assert_eq!(editor.active().cursor().y(), 6);
+
+    keys!("@@");
+    assert_eq!(editor.active().cursor().y(), 8);
+
+    keys!("qA:<Esc>kq");
+    assert_eq!(editor.registers[&'a'], "jj:<Esc>k");
+    assert_eq!(editor.active().cursor().y(), 7);
+
+    keys!(":let @b = 'kdd'<CR>gg2j5@b");
+    assert_eq!(editor.active().current(), ">");
+    assert_eq!(editor.active().cursor().y(), 0);
+
+    keys!("G");
+    assert_eq!(editor.active().cursor().y(), 8);
+
+    assert!(editor.execute("let @b kdd").is_err());
+    assert!(editor.execute("let @b = <Hyper-x>").is_err());
+    assert!(editor.execute("registers").is_ok());
+}
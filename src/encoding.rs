@@ -0,0 +1,549 @@
+use std::{borrow::Cow, fmt};
+
+const ESCAPE: u32 = 0x10FF00;
+
+const WINDOWS_1252: [Option<char>; 32] = [
+    Some('\u{20ac}'),
+    None,
+    Some('\u{201a}'),
+    Some('\u{192}'),
+    Some('\u{201e}'),
+    Some('\u{2026}'),
+    Some('\u{2020}'),
+    Some('\u{2021}'),
+    Some('\u{2c6}'),
+    Some('\u{2030}'),
+    Some('\u{160}'),
+    Some('\u{2039}'),
+    Some('\u{152}'),
+    None,
+    Some('\u{17d}'),
+    None,
+    None,
+    Some('\u{2018}'),
+    Some('\u{2019}'),
+    Some('\u{201c}'),
+    Some('\u{201d}'),
+    Some('\u{2022}'),
+    Some('\u{2013}'),
+    Some('\u{2014}'),
+    Some('\u{2dc}'),
+    Some('\u{2122}'),
+    Some('\u{161}'),
+    Some('\u{203a}'),
+    Some('\u{153}'),
+    None,
+    Some('\u{17e}'),
+    Some('\u{178}'),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Encoding {
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+    Latin1,
+    Windows1252,
+}
+
+const NAMES: &[(&str, Encoding)] = &[
+    ("utf-8", Encoding::Utf8),
+    ("utf8", Encoding::Utf8),
+    ("utf-16le", Encoding::Utf16Le),
+    ("utf-16be", Encoding::Utf16Be),
+    ("latin1", Encoding::Latin1),
+    ("iso-8859-1", Encoding::Latin1),
+    ("windows-1252", Encoding::Windows1252),
+    ("cp1252", Encoding::Windows1252),
+];
+
+impl Encoding {
+    pub fn named(name: &str) -> Option<Self> {
+        NAMES
+            .iter()
+            .find(|(encoding, _)| encoding.eq_ignore_ascii_case(name))
+            .map(|(_, encoding)| *encoding)
+    }
+
+    pub fn name(&self) -> &'static str {
+        NAMES
+            .iter()
+            .find(|(_, encoding)| encoding == self)
+            .map_or("", |(name, _)| name)
+    }
+
+    pub fn bom(&self) -> &'static [u8] {
+        match self {
+            Encoding::Utf8 => b"\xef\xbb\xbf",
+            Encoding::Utf16Le => b"\xff\xfe",
+            Encoding::Utf16Be => b"\xfe\xff",
+            Encoding::Latin1 | Encoding::Windows1252 => b"",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Ending {
+    Lf,
+    Crlf,
+    Cr,
+}
+
+const ENDINGS: &[(&str, Ending)] = &[
+    ("unix", Ending::Lf),
+    ("dos", Ending::Crlf),
+    ("mac", Ending::Cr),
+    ("lf", Ending::Lf),
+    ("crlf", Ending::Crlf),
+    ("cr", Ending::Cr),
+];
+
+impl Ending {
+    pub fn named(name: &str) -> Option<Self> {
+        ENDINGS
+            .iter()
+            .find(|(ending, _)| ending.eq_ignore_ascii_case(name))
+            .map(|(_, ending)| *ending)
+    }
+
+    pub fn name(&self) -> &'static str {
+        ENDINGS
+            .iter()
+            .find(|(_, ending)| ending == self)
+            .map_or("", |(name, _)| name)
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Ending::Lf => "\n",
+            Ending::Crlf => "\r\n",
+            Ending::Cr => "\r",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Format {
+    pub encoding: Encoding,
+    pub bom: bool,
+    pub ending: Ending,
+}
+
+impl Default for Format {
+    fn default() -> Self {
+        Self {
+            encoding: Encoding::Utf8,
+            bom: false,
+            ending: Ending::Lf,
+        }
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.encoding.name())?;
+
+        if self.bom {
+            write!(f, " bom")?;
+        }
+
+        if self.ending != Ending::Lf {
+            write!(f, " {}", self.ending.name())?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Decoded {
+    Valid(String),
+    Escaped(String, usize),
+    Lossy(String),
+}
+
+pub fn decode(bytes: Vec<u8>) -> (Format, Decoded) {
+    for encoding in [Encoding::Utf8, Encoding::Utf16Le, Encoding::Utf16Be] {
+        if let Some(rest) = bytes.strip_prefix(encoding.bom()) {
+            let format = Format {
+                encoding,
+                bom: true,
+                ..Format::default()
+            };
+
+            let decoded = match encoding {
+                Encoding::Utf8 => utf8(rest.to_vec()),
+                encoding => utf16(rest, encoding == Encoding::Utf16Be),
+            };
+
+            return (format, decoded);
+        }
+    }
+
+    let encoding = match guess(&bytes) {
+        Some(encoding) => encoding,
+        None => return (Format::default(), utf8(bytes)),
+    };
+
+    let decoded = match encoding {
+        Encoding::Utf16Le | Encoding::Utf16Be => utf16(&bytes, encoding == Encoding::Utf16Be),
+        encoding => Decoded::Valid(bytes.iter().map(|byte| single(*byte, encoding)).collect()),
+    };
+
+    (
+        Format {
+            encoding,
+            ..Format::default()
+        },
+        decoded,
+    )
+}
+
+fn guess(bytes: &[u8]) -> Option<Encoding> {
+    let pairs = bytes.len() / 2;
+
+    let zeros = |parity| {
+        bytes
+            .iter()
+            .skip(parity)
+            .step_by(2)
+            .filter(|byte| **byte == 0)
+            .count()
+    };
+
+    if !bytes.is_empty() && bytes.len().is_multiple_of(2) {
+        match (zeros(0), zeros(1)) {
+            (0, odd) if odd * 2 > pairs => return Some(Encoding::Utf16Le),
+            (even, 0) if even * 2 > pairs => return Some(Encoding::Utf16Be),
+            _ => {}
+        };
+    }
+
+    if std::str::from_utf8(bytes).is_ok()
+        || bytes.contains(&0)
+        || bytes.utf8_chunks().any(|chunk| {
+            chunk
+                .valid()
+                .chars()
+                .any(|character| character.len_utf8() > 1)
+        })
+    {
+        return None;
+    }
+
+    match bytes
+        .iter()
+        .any(|byte| matches!(byte, 0x81 | 0x8d | 0x8f | 0x90 | 0x9d))
+    {
+        true => Some(Encoding::Latin1),
+        false => Some(Encoding::Windows1252),
+    }
+}
+
+fn utf8(bytes: Vec<u8>) -> Decoded {
+    let bytes = match String::from_utf8(bytes) {
+        Ok(text) => return Decoded::Valid(text),
+        Err(error) => error.into_bytes(),
+    };
+
+    let mut text = String::with_capacity(bytes.len());
+    let mut invalid = 0;
+
+    for chunk in bytes.utf8_chunks() {
+        if chunk
+            .valid()
+            .chars()
+            .any(|character| unescape(character).is_some())
+        {
+            return Decoded::Lossy(String::from_utf8_lossy(&bytes).into_owned());
+        }
+
+        text.push_str(chunk.valid());
+        text.extend(
+            chunk
+                .invalid()
+                .iter()
+                .filter_map(|byte| char::from_u32(ESCAPE + *byte as u32)),
+        );
+
+        invalid += chunk.invalid().len();
+    }
+
+    Decoded::Escaped(text, invalid)
+}
+
+fn utf16(bytes: &[u8], big: bool) -> Decoded {
+    let units = bytes.chunks_exact(2).map(|pair| match big {
+        true => u16::from_be_bytes([pair[0], pair[1]]),
+        false => u16::from_le_bytes([pair[0], pair[1]]),
+    });
+
+    let mut lossy = !bytes.len().is_multiple_of(2);
+
+    let text = char::decode_utf16(units)
+        .map(|character| {
+            character.unwrap_or_else(|_| {
+                lossy = true;
+
+                char::REPLACEMENT_CHARACTER
+            })
+        })
+        .collect();
+
+    match lossy {
+        true => Decoded::Lossy(text),
+        false => Decoded::Valid(text),
+    }
+}
+
+fn single(byte: u8, encoding: Encoding) -> char {
+    match (encoding, byte) {
+        (Encoding::Windows1252, 0x80..=0x9f) => {
+            WINDOWS_1252[byte as usize - 0x80].unwrap_or(byte as char)
+        }
+        _ => byte as char,
+    }
+}
+
+fn byte(character: char, encoding: Encoding) -> Option<u8> {
+    let code = u8::try_from(character as u32).ok();
+
+    match (encoding, code) {
+        (Encoding::Windows1252, Some(byte @ 0x80..=0x9f))
+            if single(byte, encoding) != character =>
+        {
+            None
+        }
+        (Encoding::Windows1252, None) => WINDOWS_1252
+            .iter()
+            .position(|mapped| *mapped == Some(character))
+            .map(|index| index as u8 + 0x80),
+        (_, code) => code,
+    }
+}
+
+pub fn normalize(text: String) -> (String, Ending, bool) {
+    if !text.contains('\r') {
+        return (text, Ending::Lf, false);
+    }
+
+    let crlf = text.matches("\r\n").count();
+    let cr = text.matches('\r').count() - crlf;
+    let lf = text.matches('\n').count() - crlf;
+
+    let ending = match (lf, crlf, cr) {
+        (lf, crlf, cr) if crlf >= lf && crlf >= cr => Ending::Crlf,
+        (lf, _, cr) if cr > lf => Ending::Cr,
+        _ => Ending::Lf,
+    };
+
+    let (text, cr) = match ending {
+        Ending::Lf => (text.replace("\r\n", "\n"), 0),
+        _ => (text.replace("\r\n", "\n").replace('\r', "\n"), cr),
+    };
+
+    let mixed = [lf, crlf, cr].iter().filter(|count| **count > 0).count() > 1;
+
+    (text, ending, mixed)
+}
+
+pub fn encode(text: &str, format: Format, escaped: bool) -> Result<Cow<'_, [u8]>, usize> {
+    if format.ending != Ending::Lf {
+        let text = text.replace('\n', format.ending.as_str());
+        let format = Format {
+            ending: Ending::Lf,
+            ..format
+        };
+
+        return encode(&text, format, escaped).map(|bytes| Cow::Owned(bytes.into_owned()));
+    }
+
+    let unescape = |character| match escaped {
+        true => unescape(character),
+        false => None,
+    };
+
+    let escapes = text
+        .chars()
+        .filter(|character| unescape(*character).is_some())
+        .count();
+
+    let bom = match format.bom {
+        true => format.encoding.bom(),
+        false => b"",
+    };
+
+    if format.encoding == Encoding::Utf8 && escapes == 0 && bom.is_empty() {
+        return Ok(Cow::Borrowed(text.as_bytes()));
+    }
+
+    let mut bytes = Vec::with_capacity(bom.len() + text.len());
+    let mut unrepresentable = 0;
+
+    bytes.extend_from_slice(bom);
+
+    for character in text.chars() {
+        match (format.encoding, unescape(character)) {
+            (Encoding::Utf8, Some(byte)) => bytes.push(byte),
+            (Encoding::Utf8, None) => {
+                bytes.extend_from_slice(character.encode_utf8(&mut [0; 4]).as_bytes())
+            }
+            (_, Some(_)) => unrepresentable += 1,
+            (Encoding::Utf16Le | Encoding::Utf16Be, None) => {
+                for unit in character.encode_utf16(&mut [0; 2]) {
+                    bytes.extend_from_slice(&match format.encoding {
+                        Encoding::Utf16Be => unit.to_be_bytes(),
+                        _ => unit.to_le_bytes(),
+                    });
+                }
+            }
+            (encoding, None) => match byte(character, encoding) {
+                Some(byte) => bytes.push(byte),
+                None => unrepresentable += 1,
+            },
+        }
+    }
+
+    match unrepresentable {
+        0 => Ok(Cow::Owned(bytes)),
+        count => Err(count),
+    }
+}
+
+pub fn escaped(grapheme: &str) -> Option<String> {
+    if !grapheme
+        .chars()
+        .any(|character| unescape(character).is_some())
+    {
+        return None;
+    }
+
+    Some(
+        grapheme
+            .chars()
+            .map(|character| match unescape(character) {
+                Some(byte) => format!("\\x{:02X}", byte),
+                None => character.to_string(),
+            })
+            .collect(),
+    )
+}
+
+fn unescape(character: char) -> Option<u8> {
+    match character as u32 {
+        code if (ESCAPE + 0x80..=ESCAPE + 0xFF).contains(&code) => Some((code - ESCAPE) as u8),
+        _ => None,
+    }
+}
+
+#[test]
+fn round_trip() {
+    let utf8 = Format::default();
+    let bytes = b"ok \xff\xfe caf\xc3\xa9 \xe2\x82".to_vec();
+
+    let text = match decode(bytes.clone()) {
+        (format, Decoded::Escaped(text, 4)) if format == utf8 => text,
+        decoded => panic!("{:?}", decoded),
+    };
+
+    assert!(text.starts_with("ok "));
+    assert!(text.contains(" caf\u{e9} "));
+    assert_eq!(encode(&text, utf8, true).unwrap(), &bytes[..]);
+    assert_eq!(escaped(text.get(3..7).unwrap()).as_deref(), Some("\\xFF"));
+    assert_eq!(escaped("e"), None);
+
+    assert_eq!(
+        decode(b"plain".to_vec()),
+        (utf8, Decoded::Valid("plain".into()))
+    );
+    assert!(matches!(
+        encode("plain", utf8, false),
+        Ok(Cow::Borrowed(b"plain"))
+    ));
+
+    match decode("\u{10ffff}\u{ff}".bytes().chain([0xff]).collect()) {
+        (_, Decoded::Lossy(text)) => assert!(text.ends_with('\u{fffd}')),
+        decoded => panic!("{:?}", decoded),
+    }
+
+    let private = "\u{10ff80}\u{10ffff}\n".as_bytes().to_vec();
+
+    let text = match decode(private.clone()) {
+        (_, Decoded::Valid(text)) => text,
+        decoded => panic!("{:?}", decoded),
+    };
+
+    assert_eq!(encode(&text, utf8, false).unwrap(), &private[..]);
+}
+
+#[test]
+fn formats() {
+    let check = |bytes: &[u8], encoding, bom, expected: &str| {
+        let (format, decoded) = decode(bytes.to_vec());
+
+        assert_eq!(
+            format,
+            Format {
+                encoding,
+                bom,
+                ..Format::default()
+            }
+        );
+        assert_eq!(decoded, Decoded::Valid(expected.into()));
+        assert_eq!(encode(expected, format, false).unwrap(), bytes);
+    };
+
+    check(b"\xef\xbb\xbfbom", Encoding::Utf8, true, "bom");
+    check(
+        b"\xff\xfeh\0i\0=\xd8\0\xde",
+        Encoding::Utf16Le,
+        true,
+        "hi\u{1f600}",
+    );
+    check(b"\xfe\xff\0h\0\xe9", Encoding::Utf16Be, true, "h\u{e9}");
+    check(b"h\0i\0\n\0", Encoding::Utf16Le, false, "hi\n");
+    check(
+        b"\x93caf\xe9\x94",
+        Encoding::Windows1252,
+        false,
+        "\u{201c}caf\u{e9}\u{201d}",
+    );
+    check(b"\x81caf\xe9", Encoding::Latin1, false, "\u{81}caf\u{e9}");
+
+    let latin1 = Format {
+        encoding: Encoding::Latin1,
+        ..Format::default()
+    };
+
+    assert_eq!(encode("\u{20ac} and \u{4e2d}", latin1, false), Err(2));
+    assert_eq!(Encoding::named("CP1252"), Some(Encoding::Windows1252));
+    assert_eq!(
+        Format {
+            encoding: Encoding::Utf16Be,
+            bom: true,
+            ending: Ending::Crlf,
+        }
+        .to_string(),
+        "utf-16be bom dos"
+    );
+
+    assert_eq!(
+        normalize("a\r\nb\r\nc\n".into()),
+        ("a\nb\nc\n".into(), Ending::Crlf, true)
+    );
+    assert_eq!(
+        normalize("a\rb\r".into()),
+        ("a\nb\n".into(), Ending::Cr, false)
+    );
+    assert_eq!(
+        normalize("a\rb\nc\n".into()),
+        ("a\rb\nc\n".into(), Ending::Lf, false)
+    );
+
+    let dos = Format {
+        ending: Ending::Crlf,
+        ..Format::default()
+    };
+
+    assert_eq!(encode("a\nb\n", dos, false).unwrap(), &b"a\r\nb\r\n"[..]);
+}
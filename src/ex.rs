@@ -0,0 +1,454 @@
+use std::collections::HashMap;
+
+use regex::Regex;
+
+use crate::motion::line_count;
+
+const COMMANDS: &[(&str, usize)] = &[
+    ("bNext", 2),
+    ("bdelete", 2),
+    ("bnext", 2),
+    ("bprevious", 2),
+    ("buffer", 1),
+    ("buffers", 7),
+    ("cnext", 2),
+    ("copen", 4),
+    ("cprevious", 2),
+    ("delete", 1),
+    ("display", 2),
+    ("edit", 1),
+    ("encoding", 3),
+    ("endings", 3),
+    ("files", 5),
+    ("goto", 2),
+    ("grep", 2),
+    ("hex", 3),
+    ("let", 3),
+    ("ls", 2),
+    ("mksession", 3),
+    ("quit", 1),
+    ("read", 1),
+    ("recover", 3),
+    ("reload", 3),
+    ("registers", 3),
+    ("session", 3),
+    ("source", 2),
+    ("wq", 2),
+    ("write", 1),
+    ("yank", 1),
+];
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Command {
+    pub range: Option<Range>,
+    pub name: &'static str,
+    pub force: bool,
+    pub argument: String,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Range {
+    pub start: Address,
+    pub end: Option<Address>,
+    pub anchored: bool,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Address {
+    pub base: Base,
+    pub offset: isize,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Base {
+    Current,
+    Last,
+    Line(usize),
+    Mark(char),
+    Forward(String),
+    Backward(String),
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Completion {
+    Command,
+    Path,
+    Buffer,
+    Nothing,
+}
+
+pub fn parse(input: &str) -> Result<Command, Error> {
+    let input =
+        input.trim_start_matches(|character: char| character == ':' || character.is_whitespace());
+
+    let (range, rest) = range(input)?;
+
+    let rest = rest.trim_start();
+
+    let split = rest
+        .find(|character: char| !character.is_ascii_alphabetic())
+        .unwrap_or(rest.len());
+
+    let (name, rest) = rest.split_at(split);
+
+    if let ("", Some(command)) = (name, rest.strip_prefix('!')) {
+        return Ok(Command {
+            range,
+            name: "!",
+            force: false,
+            argument: command.trim().into(),
+        });
+    }
+
+    let name = match name {
+        "" => "",
+        name => resolve(name).ok_or_else(|| Error::Unknown(input.into()))?,
+    };
+
+    let (force, argument) = match rest.strip_prefix('!') {
+        Some(argument) => (true, argument),
+        None => (false, rest),
+    };
+
+    Ok(Command {
+        range,
+        name,
+        force,
+        argument: argument.trim().into(),
+    })
+}
+
+pub fn resolve(name: &str) -> Option<&'static str> {
+    COMMANDS
+        .iter()
+        .find(|(full, _)| *full == name)
+        .or_else(|| {
+            COMMANDS
+                .iter()
+                .find(|(full, least)| name.len() >= *least && full.starts_with(name))
+        })
+        .map(|(full, _)| *full)
+}
+
+pub fn names(prefix: &str) -> Vec<String> {
+    COMMANDS
+        .iter()
+        .map(|(full, _)| *full)
+        .filter(|full| full.starts_with(prefix))
+        .map(String::from)
+        .collect()
+}
+
+pub fn completion(input: &str) -> (usize, Completion) {
+    let skipped = match range(input) {
+        Ok((_, rest)) => input.len() - rest.len(),
+        Err(_) => return (input.len(), Completion::Nothing),
+    };
+
+    let rest = &input[skipped..];
+    let name_end = rest
+        .find(|character: char| !character.is_ascii_alphabetic())
+        .unwrap_or(rest.len());
+
+    if name_end == rest.len() {
+        return (skipped, Completion::Command);
+    }
+
+    let mut start = skipped + name_end;
+
+    if input[start..].starts_with('!') {
+        start += 1;
+    }
+
+    let start = input[start..]
+        .rfind(char::is_whitespace)
+        .map_or(start, |index| start + index + 1);
+
+    let completion = match resolve(&rest[..name_end]) {
+        Some("buffer" | "bdelete") => Completion::Buffer,
+        Some("edit" | "write" | "wq" | "source" | "read") => Completion::Path,
+        _ => Completion::Nothing,
+    };
+
+    (start, completion)
+}
+
+fn range(input: &str) -> Result<(Option<Range>, &str), Error> {
+    if let Some(rest) = input.strip_prefix('%') {
+        let range = Range {
+            start: Address {
+                base: Base::Line(1),
+                offset: 0,
+            },
+            end: Some(Address {
+                base: Base::Last,
+                offset: 0,
+            }),
+            anchored: false,
+        };
+
+        return Ok((Some(range), rest));
+    }
+
+    let (start, rest) = match address(input)? {
+        (Some(start), rest) => (start, rest),
+        (None, _) => return Ok((None, input)),
+    };
+
+    let (anchored, rest) = match rest.chars().next() {
+        Some(',') => (false, &rest[1..]),
+        Some(';') => (true, &rest[1..]),
+        _ => {
+            let range = Range {
+                start,
+                end: None,
+                anchored: false,
+            };
+
+            return Ok((Some(range), rest));
+        }
+    };
+
+    let (end, rest) = match address(rest)? {
+        (Some(end), rest) => (end, rest),
+        (None, rest) => (
+            Address {
+                base: Base::Current,
+                offset: 0,
+            },
+            rest,
+        ),
+    };
+
+    let range = Range {
+        start,
+        end: Some(end),
+        anchored,
+    };
+
+    Ok((Some(range), rest))
+}
+
+fn address(input: &str) -> Result<(Option<Address>, &str), Error> {
+    let mut characters = input.chars();
+
+    let (base, mut rest) = match characters.next() {
+        Some('.') => (Some(Base::Current), &input[1..]),
+        Some('$') => (Some(Base::Last), &input[1..]),
+        Some('\'') => match characters.next() {
+            Some(mark) if mark.is_ascii_lowercase() || mark == '<' || mark == '>' => {
+                (Some(Base::Mark(mark)), &input[2..])
+            }
+            _ => return Err(Error::Mark),
+        },
+        Some(delimiter @ ('/' | '?')) => {
+            let body = &input[1..];
+            let (pattern, rest) = match body.find(delimiter) {
+                Some(index) => (&body[..index], &body[index + 1..]),
+                None => (body, ""),
+            };
+
+            match delimiter {
+                '/' => (Some(Base::Forward(pattern.into())), rest),
+                _ => (Some(Base::Backward(pattern.into())), rest),
+            }
+        }
+        Some(digit) if digit.is_ascii_digit() => {
+            let end = input
+                .find(|character: char| !character.is_ascii_digit())
+                .unwrap_or(input.len());
+            let number = input[..end].parse().map_err(|_| Error::Range)?;
+
+            (Some(Base::Line(number)), &input[end..])
+        }
+        _ => (None, input),
+    };
+
+    let mut offset = 0;
+    let mut signed = false;
+
+    while let Some(sign) = rest
+        .chars()
+        .next()
+        .filter(|sign| *sign == '+' || *sign == '-')
+    {
+        let end = rest[1..]
+            .find(|character: char| !character.is_ascii_digit())
+            .map_or(rest.len(), |index| index + 1);
+        let amount = match &rest[1..end] {
+            "" => 1,
+            digits => digits.parse::<isize>().map_err(|_| Error::Range)?,
+        };
+
+        offset += if sign == '+' { amount } else { -amount };
+        signed = true;
+        rest = &rest[end..];
+    }
+
+    let address = match (base, signed) {
+        (Some(base), _) => Some(Address { base, offset }),
+        (None, true) => Some(Address {
+            base: Base::Current,
+            offset,
+        }),
+        (None, false) => None,
+    };
+
+    Ok((address, rest))
+}
+
+impl Range {
+    pub fn lines(
+        &self,
+        source: &str,
+        current: usize,
+        marks: &HashMap<char, usize>,
+    ) -> Result<(usize, usize), Error> {
+        let start = self.start.line(source, current, marks)?;
+
+        let end = match &self.end {
+            Some(end) if self.anchored => end.line(source, start, marks)?,
+            Some(end) => end.line(source, current, marks)?,
+            None => start,
+        };
+
+        if start > end {
+            return Err(Error::Backwards);
+        }
+
+        Ok((start, end))
+    }
+}
+
+impl Address {
+    pub fn line(
+        &self,
+        source: &str,
+        current: usize,
+        marks: &HashMap<char, usize>,
+    ) -> Result<usize, Error> {
+        let last = line_count(source) - 1;
+
+        let line = match &self.base {
+            Base::Current => current,
+            Base::Last => last,
+            Base::Line(number) => number.saturating_sub(1),
+            Base::Mark(mark) => {
+                let z = marks.get(mark).ok_or(Error::Unset(*mark))?;
+
+                source[..source.floor_char_boundary(*z)]
+                    .matches('\n')
+                    .count()
+            }
+            Base::Forward(pattern) => search(source, pattern, current, false)?,
+            Base::Backward(pattern) => search(source, pattern, current, true)?,
+        };
+
+        let line = line as isize + self.offset;
+
+        match line {
+            0.. if line as usize <= last => Ok(line as usize),
+            _ => Err(Error::Range),
+        }
+    }
+}
+
+fn search(source: &str, pattern: &str, current: usize, backward: bool) -> Result<usize, Error> {
+    let regex = Regex::new(pattern).map_err(|error| Error::Pattern(error.to_string()))?;
+
+    let lines = source.lines().collect::<Vec<_>>();
+    let count = lines.len().max(1);
+
+    (1..=count)
+        .map(|step| match backward {
+            true => (current + count * 2 - step) % count,
+            false => (current + step) % count,
+        })
+        .find(|index| lines.get(*index).is_some_and(|line| regex.is_match(line)))
+        .ok_or_else(|| Error::NotFound(pattern.into()))
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("Not an editor command: {0}")]
+    Unknown(String),
+    #[error("Invalid range")]
+    Range,
+    #[error("Backwards range given")]
+    Backwards,
+    #[error("Invalid mark")]
+    Mark,
+    #[error("Mark not set: {0}")]
+    Unset(char),
+    #[error("Invalid pattern: {0}")]
+    Pattern(String),
+    #[error("Pattern not found: {0}")]
+    NotFound(String),
+}
+
+#[test]
+fn commands() {
+    let command = parse("bn").unwrap();
+
+    assert_eq!((command.name, command.force), ("bnext", false));
+    assert_eq!(parse("b#").unwrap().argument, "#");
+    assert_eq!(parse(":bd! 2").unwrap().argument, "2");
+    assert!(parse("bd!").unwrap().force);
+    assert_eq!(parse("%!sort -r").unwrap().name, "!");
+    assert_eq!(parse("%!sort -r").unwrap().argument, "sort -r");
+    assert_eq!(parse("w !wc").unwrap().argument, "!wc");
+    assert_eq!(parse("buffers").unwrap().name, "buffers");
+    assert_eq!(parse("bu").unwrap().name, "buffer");
+    assert_eq!(parse("cope").unwrap().name, "copen");
+    assert_eq!(parse("enc latin1").unwrap().name, "encoding");
+    assert_eq!(parse("rec diff").unwrap().name, "recover");
+    assert!(parse("co").is_err());
+    assert!(parse("frobnicate").is_err());
+
+    assert_eq!(names("bd"), ["bdelete"]);
+
+    assert_eq!(completion("b"), (0, Completion::Command));
+    assert_eq!(completion("2,3d"), (3, Completion::Command));
+    assert_eq!(completion("e src/ma"), (2, Completion::Path));
+    assert_eq!(completion("bd!on"), (3, Completion::Buffer));
+    assert_eq!(completion("grep x"), (5, Completion::Nothing));
+}
+
+#[test]
+fn ranges() {
+    let source = include_str!("../edit");
+    let marks = HashMap::from([('a', source.find("## Canker").unwrap())]);
+
+    let lines = |input: &str, current| {
+        parse(input)
+            .unwrap()
+            .range
+            .unwrap()
+            .lines(source, current, &marks)
+    };
+
+    assert_eq!(lines("%", 4).unwrap(), (0, 10));
+    assert_eq!(lines(".", 4).unwrap(), (4, 4));
+    assert_eq!(lines(".,$", 4).unwrap(), (4, 10));
+    assert_eq!(lines("2,+2", 4).unwrap(), (1, 6));
+    assert_eq!(lines("2;+2", 4).unwrap(), (1, 3));
+    assert_eq!(lines("'a,.", 9).unwrap(), (8, 9));
+    assert_eq!(lines("/Canker/", 0).unwrap(), (2, 2));
+    assert_eq!(lines("?Canker?-1", 2).unwrap(), (9, 9));
+    assert!(matches!(lines("$,1", 0), Err(Error::Backwards)));
+    assert!(matches!(lines("12", 0), Err(Error::Range)));
+    assert!(matches!(lines("'b", 0), Err(Error::Unset('b'))));
+    assert!(matches!(lines("/nowhere/", 0), Err(Error::NotFound(_))));
+
+    let wide = HashMap::from([('a', 1)]);
+
+    assert_eq!(
+        parse("'a")
+            .unwrap()
+            .range
+            .unwrap()
+            .lines("é\nb\n", 1, &wide)
+            .unwrap(),
+        (0, 0)
+    );
+}
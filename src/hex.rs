@@ -0,0 +1,330 @@
+use crossterm::{
+    cursor::MoveTo,
+    style::{Attribute, Print, SetAttribute},
+    Command,
+};
+
+use crate::keymap::Action;
+
+const WIDTH: usize = 16;
+const GUTTER: usize = 10;
+
+pub struct Hex {
+    bytes: Vec<u8>,
+    cursor: usize,
+    top: usize,
+    edits: Vec<(usize, u8)>,
+    saved: usize,
+    typing: bool,
+}
+
+impl Hex {
+    pub fn new(bytes: Vec<u8>, offset: usize) -> Self {
+        let mut hex = Self {
+            bytes,
+            cursor: 0,
+            top: 0,
+            edits: vec![],
+            saved: 0,
+            typing: false,
+        };
+
+        hex.seek(offset * 2);
+
+        hex
+    }
+
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+
+    pub fn offset(&self) -> usize {
+        self.cursor / 2
+    }
+
+    pub fn current(&self) -> Option<u8> {
+        self.bytes.get(self.offset()).copied()
+    }
+
+    pub fn typing(&self) -> bool {
+        self.typing
+    }
+
+    pub fn modified(&self) -> bool {
+        self.edits.len() != self.saved
+    }
+
+    pub fn save(&mut self) {
+        self.saved = self.edits.len();
+    }
+
+    pub fn restore(&mut self, bytes: &[u8]) -> Result<(), String> {
+        if bytes.len() != self.bytes.len() {
+            return Err("Swap file length differs from the hex buffer".into());
+        }
+
+        for (offset, (byte, restored)) in self.bytes.iter_mut().zip(bytes).enumerate() {
+            if byte != restored {
+                self.edits.push((offset, *byte));
+                *byte = *restored;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn seek(&mut self, nibble: usize) {
+        self.cursor = nibble.min((self.bytes.len() * 2).saturating_sub(1));
+    }
+
+    pub fn perform(
+        &mut self,
+        action: Action,
+        count: Option<usize>,
+        argument: Option<char>,
+    ) -> Result<bool, String> {
+        let steps = count.unwrap_or(1);
+
+        let nibble = match action {
+            Action::Left => self.cursor.saturating_sub(steps),
+            Action::Right => self.cursor.saturating_add(steps),
+            Action::PreviousByte => (self.offset().saturating_sub(steps)) * 2,
+            Action::NextByte => (self.offset().saturating_add(steps)) * 2,
+            Action::Up => self.cursor.saturating_sub(steps * WIDTH * 2),
+            Action::Down | Action::Open => self.cursor.saturating_add(steps * WIDTH * 2),
+            Action::First => 0,
+            Action::Last => self.bytes.len().saturating_sub(1) * 2,
+            Action::Offset => count.unwrap_or(1).saturating_sub(1).saturating_mul(2),
+            Action::Replace => {
+                self.typing = true;
+
+                return Ok(true);
+            }
+            Action::ReplaceChar => {
+                for _ in 0..steps {
+                    self.write(argument.unwrap_or(' '))?;
+                }
+
+                return Ok(true);
+            }
+            Action::Backspace => self.cursor.saturating_sub(1),
+            Action::Undo => match self.edits.pop() {
+                Some((offset, byte)) => {
+                    self.bytes[offset] = byte;
+
+                    if self.saved > self.edits.len() {
+                        self.saved = usize::MAX;
+                    }
+
+                    offset * 2
+                }
+                None => return Err("Already at oldest change".into()),
+            },
+            Action::Cancel => {
+                self.typing = false;
+
+                return Ok(true);
+            }
+            Action::Prompt => return Ok(true),
+            _ => return Err("Not available in hex mode".into()),
+        };
+
+        let before = self.cursor;
+
+        self.seek(nibble);
+
+        Ok(self.cursor != before || matches!(action, Action::First | Action::Last | Action::Undo))
+    }
+
+    pub fn write(&mut self, digit: char) -> Result<(), String> {
+        let value = digit
+            .to_digit(16)
+            .ok_or_else(|| format!("Not a hex digit: {}", digit))? as u8;
+
+        let offset = self.offset();
+
+        let byte = match self.bytes.get_mut(offset) {
+            Some(byte) => byte,
+            None => return Err("Buffer is empty".into()),
+        };
+
+        self.edits.push((offset, *byte));
+
+        *byte = match self.cursor % 2 {
+            0 => (*byte & 0x0f) | value << 4,
+            _ => (*byte & 0xf0) | value,
+        };
+
+        self.seek(self.cursor + 1);
+
+        Ok(())
+    }
+
+    pub fn find(&mut self, pattern: &[u8]) -> bool {
+        if pattern.is_empty() {
+            return false;
+        }
+
+        let from = self.offset() + 1;
+
+        let found = self
+            .bytes
+            .windows(pattern.len())
+            .enumerate()
+            .skip(from)
+            .chain(self.bytes.windows(pattern.len()).enumerate().take(from))
+            .find(|(_, window)| *window == pattern)
+            .map(|(offset, _)| offset);
+
+        match found {
+            Some(offset) => {
+                self.seek(offset * 2);
+
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn scroll(&mut self, height: usize, scroll_off: usize) {
+        let row = self.offset() / WIDTH;
+
+        if row < self.top + scroll_off {
+            self.top = row.saturating_sub(scroll_off);
+        } else if row + scroll_off >= self.top + height {
+            self.top = row + scroll_off + 1 - height;
+        }
+    }
+
+    pub fn position(&self) -> (usize, usize) {
+        let column = self.offset() % WIDTH;
+
+        (
+            GUTTER + column * 3 + column / 8 + self.cursor % 2,
+            self.offset() / WIDTH - self.top,
+        )
+    }
+
+    pub fn write_rows(&self, out: &mut impl std::fmt::Write, height: usize) -> std::fmt::Result {
+        let rows = self.bytes.chunks(WIDTH).enumerate().skip(self.top);
+
+        for (index, (row, chunk)) in rows.take(height).enumerate() {
+            MoveTo(0, index as u16).write_ansi(out)?;
+            Print(format!("{:08x}  ", row * WIDTH)).write_ansi(out)?;
+
+            let cursor = self.offset().checked_sub(row * WIDTH);
+
+            for (column, byte) in chunk.iter().enumerate() {
+                let gap = if column == 7 { "  " } else { " " };
+
+                self.cell(out, cursor == Some(column), format!("{:02x}", byte))?;
+
+                Print(gap).write_ansi(out)?;
+            }
+
+            let padding = (WIDTH - chunk.len()) * 3 + usize::from(chunk.len() <= 7);
+
+            Print(format!("{:padding$} ", "")).write_ansi(out)?;
+
+            for (column, byte) in chunk.iter().enumerate() {
+                let shown = match byte {
+                    0x20..=0x7e => *byte as char,
+                    _ => '.',
+                };
+
+                self.cell(out, cursor == Some(column), shown.to_string())?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn cell(
+        &self,
+        out: &mut impl std::fmt::Write,
+        highlighted: bool,
+        text: String,
+    ) -> std::fmt::Result {
+        match highlighted {
+            true => {
+                SetAttribute(Attribute::Reverse).write_ansi(out)?;
+                Print(text).write_ansi(out)?;
+                SetAttribute(Attribute::NoReverse).write_ansi(out)
+            }
+            false => Print(text).write_ansi(out),
+        }
+    }
+}
+
+pub fn pattern(text: &str) -> Option<Vec<u8>> {
+    if let Some(quoted) = text.strip_prefix('"') {
+        let quoted = quoted.strip_suffix('"').unwrap_or(quoted);
+
+        return Some(quoted.as_bytes().to_vec());
+    }
+
+    let digits = text
+        .chars()
+        .filter(|character| !character.is_whitespace())
+        .map(|character| character.to_digit(16).map(|digit| digit as u8))
+        .collect::<Option<Vec<_>>>()?;
+
+    if digits.is_empty() || !digits.len().is_multiple_of(2) {
+        return None;
+    }
+
+    Some(
+        digits
+            .chunks(2)
+            .map(|pair| pair[0] << 4 | pair[1])
+            .collect(),
+    )
+}
+
+#[test]
+fn hex() {
+    let mut hex = Hex::new(b"\x7fELF\x02\x01\x01\x00 tail".to_vec(), 1);
+
+    assert_eq!(hex.current(), Some(b'E'));
+    assert_eq!(hex.perform(Action::Right, None, None), Ok(true));
+    assert_eq!(hex.offset(), 1);
+    assert_eq!(hex.perform(Action::NextByte, Some(2), None), Ok(true));
+    assert_eq!(hex.current(), Some(b'F'));
+
+    hex.write('4').unwrap();
+    hex.write('6').unwrap();
+
+    assert_eq!(hex.current(), Some(0x02));
+    assert!(hex.modified());
+    assert!(hex.write('g').is_err());
+
+    hex.save();
+
+    assert_eq!(
+        hex.perform(Action::ReplaceChar, Some(2), Some('a')),
+        Ok(true)
+    );
+    assert_eq!(&hex.bytes()[3..5], b"F\xaa");
+    assert_eq!(hex.perform(Action::Undo, None, None), Ok(true));
+    assert_eq!(hex.perform(Action::Undo, None, None), Ok(true));
+    assert!(!hex.modified());
+
+    assert_eq!(pattern("01 01 00"), Some(vec![1, 1, 0]));
+    assert_eq!(pattern("\"tail\""), Some(b"tail".to_vec()));
+    assert_eq!(pattern("abc"), None);
+
+    assert!(hex.find(&pattern("0100").unwrap()));
+    assert_eq!(hex.offset(), 6);
+    assert!(hex.find(&pattern("7f").unwrap()));
+    assert_eq!(hex.offset(), 0);
+    assert!(!hex.find(b"nope"));
+
+    assert_eq!(hex.perform(Action::Last, None, None), Ok(true));
+    assert_eq!(hex.offset(), hex.bytes().len() - 1);
+    assert_eq!(hex.position(), (GUTTER + 12 * 3 + 1, 0));
+    assert!(hex.perform(Action::Yank, None, None).is_err());
+}
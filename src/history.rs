@@ -0,0 +1,149 @@
+#[derive(Debug, PartialEq, Clone)]
+pub struct Edit {
+    pub start: usize,
+    pub removed: String,
+    pub inserted: String,
+}
+
+impl Edit {
+    pub fn apply(&self, source: &mut String) {
+        source.replace_range(self.start..self.start + self.removed.len(), &self.inserted);
+    }
+
+    pub fn inverse(&self) -> Self {
+        Self {
+            start: self.start,
+            removed: self.inserted.clone(),
+            inserted: self.removed.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Step {
+    edits: Vec<Edit>,
+    cursor: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct History {
+    steps: Vec<Step>,
+    position: usize,
+    saved: Option<usize>,
+}
+
+impl Default for History {
+    fn default() -> Self {
+        Self {
+            steps: vec![],
+            position: 0,
+            saved: Some(0),
+        }
+    }
+}
+
+impl History {
+    pub fn record(&mut self, edits: Vec<Edit>, cursor: usize) {
+        if edits.is_empty() {
+            return;
+        }
+
+        self.steps.truncate(self.position);
+
+        if self.saved.is_some_and(|saved| saved > self.position) {
+            self.saved = None;
+        }
+
+        self.steps.push(Step { edits, cursor });
+        self.position += 1;
+    }
+
+    pub fn undo(&mut self, source: &mut String) -> Option<(usize, Vec<Edit>)> {
+        let step = self.steps.get(self.position.checked_sub(1)?)?;
+
+        let reverted = step
+            .edits
+            .iter()
+            .rev()
+            .map(|edit| {
+                let inverse = edit.inverse();
+
+                inverse.apply(source);
+
+                inverse
+            })
+            .collect();
+
+        self.position -= 1;
+
+        Some((step.cursor, reverted))
+    }
+
+    pub fn redo(&mut self, source: &mut String) -> Option<(usize, Vec<Edit>)> {
+        let step = self.steps.get(self.position)?;
+
+        for edit in &step.edits {
+            edit.apply(source);
+        }
+
+        self.position += 1;
+
+        let start = step.edits.first()?.start;
+
+        Some((start, step.edits.clone()))
+    }
+
+    pub fn save(&mut self) {
+        self.saved = Some(self.position);
+    }
+
+    pub fn modified(&self) -> bool {
+        self.saved != Some(self.position)
+    }
+}
+
+#[test]
+fn undo_redo() {
+    let mut source = String::from(include_str!("../edit"));
+    let mut history = History::default();
+
+    let edit = Edit {
+        start: 2,
+        removed: "Jago".into(),
+        inserted: "Edit".into(),
+    };
+
+    edit.apply(&mut source);
+    history.record(vec![edit], 2);
+
+    assert!(source.starts_with("# Edit\n"));
+    assert!(history.modified());
+
+    assert_eq!(history.undo(&mut source).map(|(z, _)| z), Some(2));
+    assert_eq!(source, include_str!("../edit"));
+    assert!(!history.modified());
+    assert!(history.undo(&mut source).is_none());
+
+    assert_eq!(history.redo(&mut source).map(|(z, _)| z), Some(2));
+    assert!(source.starts_with("# Edit\n"));
+
+    history.save();
+
+    assert!(!history.modified());
+    assert!(history.redo(&mut source).is_none());
+
+    history.undo(&mut source);
+
+    let edit = Edit {
+        start: 0,
+        removed: "# ".into(),
+        inserted: "".into(),
+    };
+
+    edit.apply(&mut source);
+    history.record(vec![edit], 0);
+
+    assert!(source.starts_with("Jago\n"));
+    assert!(history.modified());
+    assert!(history.redo(&mut source).is_none());
+}
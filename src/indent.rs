@@ -0,0 +1,130 @@
+#[derive(Debug, PartialEq)]
+pub struct Prefix {
+    pub indent: usize,
+    pub marker: usize,
+    pub outer: String,
+    pub next: String,
+}
+
+impl Prefix {
+    pub fn empty(&self, line: &str) -> bool {
+        self.marker > 0 && line[self.indent + self.marker..].trim().is_empty()
+    }
+}
+
+pub fn detect(source: &str) -> Option<bool> {
+    let (tabs, spaces) = source.lines().filter(|line| !line.trim().is_empty()).fold(
+        (0, 0),
+        |(tabs, spaces), line| match line.as_bytes() {
+            [b'\t', ..] => (tabs + 1, spaces),
+            [b' ', b' ', ..] => (tabs, spaces + 1),
+            _ => (tabs, spaces),
+        },
+    );
+
+    match (tabs, spaces) {
+        (0, 0) => None,
+        (tabs, spaces) => Some(spaces > tabs),
+    }
+}
+
+pub fn leading(line: &str) -> &str {
+    &line[..line.len() - line.trim_start_matches([' ', '\t']).len()]
+}
+
+pub fn prefix(line: &str) -> Prefix {
+    let indent = leading(line);
+
+    let mut rest = &line[indent.len()..];
+    let mut next = String::from(indent);
+    let mut outer = next.clone();
+
+    while let Some(quoted) = rest.strip_prefix('>') {
+        let quoted = quoted.strip_prefix(' ').unwrap_or(quoted);
+
+        outer.clone_from(&next);
+        next.push_str("> ");
+        rest = quoted.trim_start_matches([' ', '\t']);
+    }
+
+    if let Some(item) = rest
+        .strip_prefix(['-', '*', '+'])
+        .and_then(|item| item.strip_prefix(' '))
+    {
+        outer.clone_from(&next);
+        next.push_str(&rest[..2]);
+        rest = item;
+    } else {
+        let digits = rest.len() - rest.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+
+        let delimiter = rest[digits..]
+            .strip_prefix(['.', ')'])
+            .and_then(|item| item.strip_prefix(' '))
+            .map(|item| (&rest[digits..digits + 1], item));
+
+        if let (1..=9, Some((delimiter, item))) = (digits, delimiter) {
+            let number = rest[..digits].parse::<usize>().unwrap_or(0) + 1;
+
+            outer.clone_from(&next);
+            next.push_str(&format!("{}{} ", number, delimiter));
+            rest = item;
+        }
+    }
+
+    if next.len() > indent.len() {
+        for task in ["[ ] ", "[x] ", "[X] "] {
+            if let Some(item) = rest.strip_prefix(task) {
+                next.push_str("[ ] ");
+                rest = item;
+
+                break;
+            }
+        }
+    }
+
+    Prefix {
+        indent: indent.len(),
+        marker: line.len() - indent.len() - rest.len(),
+        outer,
+        next,
+    }
+}
+
+#[test]
+fn prefixes() {
+    assert_eq!(detect(include_str!("../edit")), None);
+    assert_eq!(
+        detect("fn main() {\n    one();\n\ttwo();\n    three();\n}\n"),
+        Some(true)
+    );
+    assert_eq!(detect("- a\n\t- b\n"), Some(false));
+
+    assert_eq!(leading("\t  text  "), "\t  ");
+
+    let next = |line: &str| prefix(line).next;
+
+    assert_eq!(next("    plain"), "    ");
+    assert_eq!(next("  - item"), "  - ");
+    assert_eq!(next("* [x] done"), "* [ ] ");
+    assert_eq!(next("9. ninth"), "10. ");
+    assert_eq!(next("3) third"), "4) ");
+    assert_eq!(next("> > - quoted"), "> > - ");
+    assert_eq!(next("-not a list"), "");
+    assert_eq!(next("2024 was a year"), "");
+
+    let line = "  > 1. ";
+    let empty = prefix(line);
+
+    assert_eq!(
+        empty,
+        Prefix {
+            indent: 2,
+            marker: 5,
+            outer: "  > ".into(),
+            next: "  > 2. ".into()
+        }
+    );
+    assert!(empty.empty(line));
+    assert!(!prefix("- text").empty("- text"));
+    assert!(!prefix("  ").empty("  "));
+}
@@ -0,0 +1,822 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    time::{Duration, Instant},
+};
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    Left,
+    Down,
+    Up,
+    Right,
+    First,
+    Last,
+    Offset,
+    Pair,
+    NextHeading,
+    PreviousHeading,
+    NextByte,
+    PreviousByte,
+    Delete,
+    Yank,
+    Change,
+    ShiftRight,
+    ShiftLeft,
+    ToggleCase,
+    Insert,
+    Append,
+    InsertStart,
+    AppendEnd,
+    OpenBelow,
+    OpenAbove,
+    Replace,
+    ReplaceChar,
+    Newline,
+    Visual,
+    VisualLine,
+    VisualBlock,
+    Swap,
+    Reselect,
+    AddCursor,
+    SkipCursor,
+    RemoveCursor,
+    SplitCursors,
+    Undo,
+    Redo,
+    Repeat,
+    Mark,
+    Jump,
+    Lens,
+    Cancel,
+    Open,
+    Prompt,
+    Picker,
+    Alternate,
+    Quit,
+    Record,
+    Replay,
+    Submit,
+    Backspace,
+    Remove,
+    Home,
+    End,
+    Older,
+    Newer,
+    Complete,
+    CompleteBack,
+}
+
+const ACTIONS: &[(&str, Action)] = &[
+    ("left", Action::Left),
+    ("down", Action::Down),
+    ("up", Action::Up),
+    ("right", Action::Right),
+    ("first-line", Action::First),
+    ("last-line", Action::Last),
+    ("offset", Action::Offset),
+    ("pair", Action::Pair),
+    ("next-heading", Action::NextHeading),
+    ("previous-heading", Action::PreviousHeading),
+    ("next-byte", Action::NextByte),
+    ("previous-byte", Action::PreviousByte),
+    ("delete", Action::Delete),
+    ("yank", Action::Yank),
+    ("change", Action::Change),
+    ("shift-right", Action::ShiftRight),
+    ("shift-left", Action::ShiftLeft),
+    ("toggle-case", Action::ToggleCase),
+    ("insert", Action::Insert),
+    ("append", Action::Append),
+    ("insert-start", Action::InsertStart),
+    ("append-end", Action::AppendEnd),
+    ("open-below", Action::OpenBelow),
+    ("open-above", Action::OpenAbove),
+    ("replace", Action::Replace),
+    ("replace-char", Action::ReplaceChar),
+    ("newline", Action::Newline),
+    ("visual", Action::Visual),
+    ("visual-line", Action::VisualLine),
+    ("visual-block", Action::VisualBlock),
+    ("swap", Action::Swap),
+    ("reselect", Action::Reselect),
+    ("add-cursor", Action::AddCursor),
+    ("skip-cursor", Action::SkipCursor),
+    ("remove-cursor", Action::RemoveCursor),
+    ("split-cursors", Action::SplitCursors),
+    ("undo", Action::Undo),
+    ("redo", Action::Redo),
+    ("repeat", Action::Repeat),
+    ("mark", Action::Mark),
+    ("jump", Action::Jump),
+    ("lens", Action::Lens),
+    ("cancel", Action::Cancel),
+    ("open", Action::Open),
+    ("prompt", Action::Prompt),
+    ("picker", Action::Picker),
+    ("alternate", Action::Alternate),
+    ("quit", Action::Quit),
+    ("record", Action::Record),
+    ("replay", Action::Replay),
+    ("submit", Action::Submit),
+    ("backspace", Action::Backspace),
+    ("remove", Action::Remove),
+    ("home", Action::Home),
+    ("end", Action::End),
+    ("older", Action::Older),
+    ("newer", Action::Newer),
+    ("complete", Action::Complete),
+    ("complete-back", Action::CompleteBack),
+];
+
+const NORMAL: &[(&str, Action)] = &[
+    ("h", Action::Left),
+    ("j", Action::Down),
+    ("k", Action::Up),
+    ("l", Action::Right),
+    ("gg", Action::First),
+    ("G", Action::Last),
+    ("go", Action::Offset),
+    ("%", Action::Pair),
+    ("]]", Action::NextHeading),
+    ("[[", Action::PreviousHeading),
+    ("d", Action::Delete),
+    ("y", Action::Yank),
+    ("c", Action::Change),
+    (">", Action::ShiftRight),
+    ("<lt>", Action::ShiftLeft),
+    ("~", Action::ToggleCase),
+    ("i", Action::Insert),
+    ("a", Action::Append),
+    ("I", Action::InsertStart),
+    ("A", Action::AppendEnd),
+    ("o", Action::OpenBelow),
+    ("O", Action::OpenAbove),
+    ("R", Action::Replace),
+    ("r", Action::ReplaceChar),
+    ("v", Action::Visual),
+    ("V", Action::VisualLine),
+    ("<C-v>", Action::VisualBlock),
+    ("gv", Action::Reselect),
+    ("<C-d>", Action::AddCursor),
+    ("<C-k>", Action::SkipCursor),
+    ("<C-x>", Action::RemoveCursor),
+    ("u", Action::Undo),
+    ("<C-r>", Action::Redo),
+    (".", Action::Repeat),
+    ("m", Action::Mark),
+    ("'", Action::Jump),
+    ("<C-n>", Action::Lens),
+    ("<Esc>", Action::Cancel),
+    ("<CR>", Action::Open),
+    (":", Action::Prompt),
+    ("<C-p>", Action::Picker),
+    ("<C-^>", Action::Alternate),
+    ("<C-6>", Action::Alternate),
+    ("q", Action::Record),
+    ("@", Action::Replay),
+];
+
+const VISUAL: &[(&str, Action)] = &[
+    ("h", Action::Left),
+    ("j", Action::Down),
+    ("k", Action::Up),
+    ("l", Action::Right),
+    ("gg", Action::First),
+    ("G", Action::Last),
+    ("go", Action::Offset),
+    ("%", Action::Pair),
+    ("]]", Action::NextHeading),
+    ("[[", Action::PreviousHeading),
+    ("'", Action::Jump),
+    ("d", Action::Delete),
+    ("x", Action::Delete),
+    ("y", Action::Yank),
+    ("c", Action::Change),
+    (">", Action::ShiftRight),
+    ("<lt>", Action::ShiftLeft),
+    ("~", Action::ToggleCase),
+    ("o", Action::Swap),
+    ("v", Action::Visual),
+    ("V", Action::VisualLine),
+    ("<C-v>", Action::VisualBlock),
+    ("<C-l>", Action::SplitCursors),
+    ("<Esc>", Action::Cancel),
+    ("<CR>", Action::Open),
+    (":", Action::Prompt),
+];
+
+const HEX: &[(&str, Action)] = &[("w", Action::NextByte), ("b", Action::PreviousByte)];
+
+const INSERT: &[(&str, Action)] = &[
+    ("<Esc>", Action::Cancel),
+    ("<C-c>", Action::Cancel),
+    ("<CR>", Action::Newline),
+    ("<BS>", Action::Backspace),
+    ("<Del>", Action::Remove),
+    ("<Left>", Action::Left),
+    ("<Right>", Action::Right),
+    ("<Up>", Action::Up),
+    ("<Down>", Action::Down),
+];
+
+const PROMPT: &[(&str, Action)] = &[
+    ("<CR>", Action::Submit),
+    ("<Esc>", Action::Cancel),
+    ("<C-c>", Action::Cancel),
+    ("<BS>", Action::Backspace),
+    ("<Del>", Action::Remove),
+    ("<Left>", Action::Left),
+    ("<Right>", Action::Right),
+    ("<Home>", Action::Home),
+    ("<C-b>", Action::Home),
+    ("<End>", Action::End),
+    ("<C-e>", Action::End),
+    ("<Up>", Action::Older),
+    ("<C-p>", Action::Older),
+    ("<Down>", Action::Newer),
+    ("<C-n>", Action::Newer),
+    ("<Tab>", Action::Complete),
+    ("<BackTab>", Action::CompleteBack),
+];
+
+const LIMIT: usize = 1000;
+
+impl Action {
+    pub fn named(name: &str) -> Option<Self> {
+        ACTIONS
+            .iter()
+            .find(|(action, _)| *action == name)
+            .map(|(_, action)| *action)
+    }
+
+    fn takes_argument(&self) -> bool {
+        matches!(
+            self,
+            Action::Mark | Action::Jump | Action::Record | Action::Replay | Action::ReplaceChar
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Mode {
+    Normal,
+    Hex,
+    Visual,
+    Insert,
+    Prompt,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Target {
+    Action(Action),
+    Keys(Vec<KeyEvent>, bool),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Step {
+    Perform {
+        action: Action,
+        count: Option<usize>,
+        argument: Option<char>,
+    },
+    Key(KeyEvent),
+    Recursive,
+}
+
+#[derive(Debug, Clone, Default)]
+struct Node {
+    target: Option<Target>,
+    children: HashMap<KeyEvent, Node>,
+}
+
+impl Node {
+    fn insert(&mut self, keys: &[KeyEvent], target: Target) {
+        let node = keys
+            .iter()
+            .fold(self, |node, key| node.children.entry(*key).or_default());
+
+        node.target = Some(target);
+    }
+}
+
+pub struct Keymap {
+    defaults: HashMap<Mode, Node>,
+    merged: HashMap<Mode, Node>,
+    queue: VecDeque<(KeyEvent, bool)>,
+    pending: Vec<(KeyEvent, bool)>,
+    count: Option<usize>,
+    argument: Option<(Action, Option<usize>)>,
+    expansions: usize,
+    since: Option<Instant>,
+    timeout: Duration,
+    recording: bool,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        let defaults = [
+            (Mode::Normal, &[NORMAL][..]),
+            (Mode::Hex, &[NORMAL, HEX]),
+            (Mode::Visual, &[VISUAL]),
+            (Mode::Insert, &[INSERT]),
+            (Mode::Prompt, &[PROMPT]),
+        ]
+        .into_iter()
+        .map(|(mode, tables)| {
+            let mut root = Node::default();
+
+            for (notation, action) in tables.iter().copied().flatten() {
+                if let Ok(keys) = parse(notation) {
+                    root.insert(&keys, Target::Action(*action));
+                }
+            }
+
+            (mode, root)
+        })
+        .collect::<HashMap<_, _>>();
+
+        Self {
+            merged: defaults.clone(),
+            defaults,
+            queue: VecDeque::new(),
+            pending: vec![],
+            count: None,
+            argument: None,
+            expansions: 0,
+            since: None,
+            timeout: Duration::from_millis(1000),
+            recording: false,
+        }
+    }
+}
+
+impl Keymap {
+    pub fn configure<'a>(
+        &mut self,
+        bindings: impl IntoIterator<Item = (Mode, &'a [KeyEvent], &'a Target)>,
+        timeout: Duration,
+    ) {
+        self.merged = self.defaults.clone();
+
+        for (mode, keys, target) in bindings {
+            let modes = match mode {
+                Mode::Normal => &[Mode::Normal, Mode::Hex][..],
+                _ => &[mode],
+            };
+
+            for mode in modes {
+                if let Some(root) = self.merged.get_mut(mode) {
+                    root.insert(keys, target.clone());
+                }
+            }
+        }
+
+        self.timeout = timeout;
+    }
+
+    pub fn push(&mut self, key: KeyEvent) {
+        self.queue.push_back((normalize(key), true));
+    }
+
+    pub fn inject(&mut self, keys: &[KeyEvent]) {
+        self.expansions += 1;
+
+        for key in keys.iter().rev() {
+            self.queue.push_front((normalize(*key), true));
+        }
+    }
+
+    pub fn record(&mut self, recording: bool) {
+        self.recording = recording;
+    }
+
+    pub fn expired(&self) -> bool {
+        self.since
+            .is_some_and(|since| since.elapsed() >= self.timeout)
+    }
+
+    pub fn next(&mut self, mode: Mode, expired: bool) -> Option<Step> {
+        loop {
+            if self.expansions > LIMIT {
+                self.reset();
+
+                return Some(Step::Recursive);
+            }
+
+            if let Some((action, count)) = self.argument {
+                let (key, _) = self.queue.pop_front()?;
+
+                self.argument = None;
+
+                let argument = match key.code {
+                    KeyCode::Char(argument) => argument,
+                    KeyCode::Enter => '\n',
+                    KeyCode::Tab => '\t',
+                    _ => continue,
+                };
+
+                return Some(Step::Perform {
+                    action,
+                    count,
+                    argument: Some(argument),
+                });
+            }
+
+            if self.pending.is_empty() {
+                let (key, remap) = match self.queue.pop_front() {
+                    Some(next) => next,
+                    None => {
+                        self.expansions = 0;
+
+                        return None;
+                    }
+                };
+
+                if matches!(mode, Mode::Normal | Mode::Hex | Mode::Visual) && self.counts(&key) {
+                    continue;
+                }
+
+                self.pending.push((key, remap));
+            }
+
+            let roots = match self.pending[0].1 {
+                true => &self.merged,
+                false => &self.defaults,
+            };
+
+            let mut node = roots.get(&mode)?;
+            let mut longest = None;
+            let mut complete = true;
+
+            for (index, (key, _)) in self.pending.iter().enumerate() {
+                match node.children.get(key) {
+                    Some(child) => node = child,
+                    None => {
+                        complete = false;
+                        break;
+                    }
+                }
+
+                if let Some(target) = &node.target {
+                    longest = Some((index + 1, target.clone()));
+                }
+            }
+
+            if complete && !node.children.is_empty() {
+                if let Some(next) = self.queue.pop_front() {
+                    self.pending.push(next);
+
+                    continue;
+                }
+
+                if !expired {
+                    self.since.get_or_insert_with(Instant::now);
+
+                    return None;
+                }
+            }
+
+            self.since = None;
+
+            let (consumed, target) = match longest {
+                Some(longest) => longest,
+                None => {
+                    let (key, _) = self.pending.remove(0);
+
+                    self.requeue();
+                    self.count = None;
+
+                    return Some(Step::Key(key));
+                }
+            };
+
+            self.pending.drain(..consumed);
+            self.requeue();
+
+            match target {
+                Target::Action(Action::Record) if self.recording => {
+                    return Some(Step::Perform {
+                        action: Action::Record,
+                        count: self.count.take(),
+                        argument: None,
+                    })
+                }
+                Target::Action(action) if action.takes_argument() => {
+                    self.argument = Some((action, self.count.take()));
+                }
+                Target::Action(action) => {
+                    return Some(Step::Perform {
+                        action,
+                        count: self.count.take(),
+                        argument: None,
+                    })
+                }
+                Target::Keys(keys, recursive) => {
+                    self.expansions += 1;
+
+                    for key in keys.into_iter().rev() {
+                        self.queue.push_front((key, recursive));
+                    }
+                }
+            }
+        }
+    }
+
+    fn counts(&mut self, key: &KeyEvent) -> bool {
+        let digit = match key.code {
+            KeyCode::Char(code) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                code.to_digit(10)
+            }
+            _ => None,
+        };
+
+        match (digit, self.count) {
+            (Some(0), None) | (None, _) => false,
+            (Some(digit), count) => {
+                self.count = Some(count.unwrap_or(0) * 10 + digit as usize);
+
+                true
+            }
+        }
+    }
+
+    fn requeue(&mut self) {
+        for pending in self.pending.drain(..).rev() {
+            self.queue.push_front(pending);
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.queue.clear();
+        self.pending.clear();
+        self.count = None;
+        self.argument = None;
+        self.expansions = 0;
+        self.since = None;
+    }
+}
+
+pub fn parse(notation: &str) -> Result<Vec<KeyEvent>, String> {
+    let mut keys = vec![];
+    let mut rest = notation;
+
+    while let Some(character) = rest.chars().next() {
+        let special = match character {
+            '<' => rest.find('>').map(|end| &rest[1..end]),
+            _ => None,
+        };
+
+        match special {
+            Some(special) => {
+                keys.push(key(special).ok_or_else(|| format!("<{}>", special))?);
+                rest = &rest[special.len() + 2..];
+            }
+            None => {
+                keys.push(KeyEvent::new(KeyCode::Char(character), KeyModifiers::NONE));
+                rest = &rest[character.len_utf8()..];
+            }
+        }
+    }
+
+    Ok(keys)
+}
+
+pub fn notation(keys: &[KeyEvent]) -> String {
+    keys.iter()
+        .map(|key| {
+            let mut modifiers = String::new();
+
+            for (modifier, prefix) in [
+                (KeyModifiers::CONTROL, "C-"),
+                (KeyModifiers::ALT, "A-"),
+                (KeyModifiers::SHIFT, "S-"),
+            ] {
+                if key.modifiers.contains(modifier) {
+                    modifiers.push_str(prefix);
+                }
+            }
+
+            let name = match key.code {
+                KeyCode::Esc => "Esc".into(),
+                KeyCode::Enter => "CR".into(),
+                KeyCode::Tab => "Tab".into(),
+                KeyCode::BackTab => "BackTab".into(),
+                KeyCode::Backspace => "BS".into(),
+                KeyCode::Delete => "Del".into(),
+                KeyCode::Char(' ') => "Space".into(),
+                KeyCode::Char('<') => "lt".into(),
+                KeyCode::Up => "Up".into(),
+                KeyCode::Down => "Down".into(),
+                KeyCode::Left => "Left".into(),
+                KeyCode::Right => "Right".into(),
+                KeyCode::Home => "Home".into(),
+                KeyCode::End => "End".into(),
+                KeyCode::F(number) => format!("F{}", number),
+                KeyCode::Char(character) if modifiers.is_empty() || modifiers == "S-" => {
+                    return character.to_string()
+                }
+                KeyCode::Char(character) => character.to_string(),
+                _ => return String::new(),
+            };
+
+            format!("<{}{}>", modifiers, name)
+        })
+        .collect()
+}
+
+fn key(special: &str) -> Option<KeyEvent> {
+    let mut modifiers = KeyModifiers::NONE;
+    let mut name = special;
+
+    while let Some((modifier, rest)) = name.split_once('-').filter(|(_, rest)| !rest.is_empty()) {
+        modifiers |= match modifier.to_ascii_uppercase().as_str() {
+            "C" => KeyModifiers::CONTROL,
+            "A" | "M" => KeyModifiers::ALT,
+            "S" => KeyModifiers::SHIFT,
+            _ => return None,
+        };
+
+        name = rest;
+    }
+
+    let code = match name.to_ascii_lowercase().as_str() {
+        "esc" => KeyCode::Esc,
+        "cr" | "enter" | "return" => KeyCode::Enter,
+        "tab" => KeyCode::Tab,
+        "backtab" => KeyCode::BackTab,
+        "bs" | "backspace" => KeyCode::Backspace,
+        "del" => KeyCode::Delete,
+        "space" => KeyCode::Char(' '),
+        "lt" => KeyCode::Char('<'),
+        "up" => KeyCode::Up,
+        "down" => KeyCode::Down,
+        "left" => KeyCode::Left,
+        "right" => KeyCode::Right,
+        "home" => KeyCode::Home,
+        "end" => KeyCode::End,
+        function if function.starts_with('f') && function.len() > 1 => {
+            KeyCode::F(function[1..].parse().ok()?)
+        }
+        _ => {
+            let mut characters = name.chars();
+
+            match (characters.next(), characters.next()) {
+                (Some(character), None) if modifiers.contains(KeyModifiers::CONTROL) => {
+                    KeyCode::Char(character.to_ascii_lowercase())
+                }
+                (Some(character), None) => KeyCode::Char(character),
+                _ => return None,
+            }
+        }
+    };
+
+    Some(normalize(KeyEvent::new(code, modifiers)))
+}
+
+fn normalize(key: KeyEvent) -> KeyEvent {
+    match key.code {
+        KeyCode::BackTab => KeyEvent::new(key.code, key.modifiers - KeyModifiers::SHIFT),
+        KeyCode::Char(character) if key.modifiers.contains(KeyModifiers::SHIFT) => KeyEvent::new(
+            KeyCode::Char(character.to_uppercase().next().unwrap_or(character)),
+            key.modifiers - KeyModifiers::SHIFT,
+        ),
+        _ => key,
+    }
+}
+
+#[test]
+fn sequences() {
+    let mut keymap = Keymap::default();
+
+    let bindings = [
+        (
+            Mode::Normal,
+            parse("Q").unwrap(),
+            Target::Keys(parse("gg").unwrap(), true),
+        ),
+        (
+            Mode::Normal,
+            parse("gq").unwrap(),
+            Target::Keys(parse("Q").unwrap(), false),
+        ),
+        (
+            Mode::Normal,
+            parse("d").unwrap(),
+            Target::Action(Action::Yank),
+        ),
+        (
+            Mode::Normal,
+            parse("dx").unwrap(),
+            Target::Action(Action::Delete),
+        ),
+        (
+            Mode::Normal,
+            parse("X").unwrap(),
+            Target::Keys(parse("X").unwrap(), true),
+        ),
+    ];
+
+    keymap.configure(
+        bindings
+            .iter()
+            .map(|(mode, keys, target)| (*mode, &keys[..], target)),
+        Duration::from_millis(1000),
+    );
+
+    let mut run = |notation: &str, expired: bool| {
+        for key in parse(notation).unwrap() {
+            keymap.push(key);
+        }
+
+        std::iter::from_fn(|| keymap.next(Mode::Normal, expired)).collect::<Vec<_>>()
+    };
+
+    let perform = |action, count, argument| Step::Perform {
+        action,
+        count,
+        argument,
+    };
+
+    assert_eq!(
+        run("12]]", false),
+        [perform(Action::NextHeading, Some(12), None)]
+    );
+    assert_eq!(run("Q", false), [perform(Action::First, None, None)]);
+    assert_eq!(run("gq", false), [Step::Key(parse("Q").unwrap()[0])]);
+    assert_eq!(
+        run("3ma", false),
+        [perform(Action::Mark, Some(3), Some('a'))]
+    );
+    assert_eq!(run("d", false), []);
+    assert_eq!(run("", true), [perform(Action::Yank, None, None)]);
+    assert_eq!(
+        run("dj", false),
+        [
+            perform(Action::Yank, None, None),
+            perform(Action::Down, None, None)
+        ]
+    );
+    assert_eq!(run("dx", false), [perform(Action::Delete, None, None)]);
+    assert_eq!(
+        run("gz0", false),
+        [
+            Step::Key(parse("g").unwrap()[0]),
+            Step::Key(parse("z").unwrap()[0]),
+            Step::Key(parse("0").unwrap()[0])
+        ]
+    );
+    assert_eq!(run("X", false), [Step::Recursive]);
+    assert_eq!(run("k", false), [perform(Action::Up, None, None)]);
+
+    keymap.push(KeyEvent::new(KeyCode::Char('G'), KeyModifiers::SHIFT));
+    assert_eq!(
+        keymap.next(Mode::Normal, false),
+        Some(perform(Action::Last, None, None))
+    );
+
+    keymap.push(KeyEvent::new(KeyCode::Char('w'), KeyModifiers::NONE));
+    assert_eq!(
+        keymap.next(Mode::Normal, false),
+        Some(Step::Key(parse("w").unwrap()[0]))
+    );
+
+    keymap.push(KeyEvent::new(KeyCode::Char('w'), KeyModifiers::NONE));
+    assert_eq!(
+        keymap.next(Mode::Hex, false),
+        Some(perform(Action::NextByte, None, None))
+    );
+
+    keymap.push(KeyEvent::new(KeyCode::Char('k'), KeyModifiers::NONE));
+    assert_eq!(
+        keymap.next(Mode::Hex, false),
+        Some(perform(Action::Up, None, None))
+    );
+
+    keymap.push(KeyEvent::new(KeyCode::BackTab, KeyModifiers::SHIFT));
+    assert_eq!(
+        keymap.next(Mode::Prompt, false),
+        Some(perform(Action::CompleteBack, None, None))
+    );
+    assert_eq!(
+        parse("<S-BackTab><S-g>").unwrap(),
+        parse("<BackTab>G").unwrap()
+    );
+
+    assert_eq!(
+        parse("<lt><Esc>a<C-S-Tab>").unwrap(),
+        [
+            KeyEvent::new(KeyCode::Char('<'), KeyModifiers::NONE),
+            KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE),
+            KeyEvent::new(KeyCode::Char('a'), KeyModifiers::NONE),
+            KeyEvent::new(KeyCode::Tab, KeyModifiers::CONTROL | KeyModifiers::SHIFT),
+        ]
+    );
+    assert!(parse("<Hyper-x>").is_err());
+
+    assert_eq!(
+        notation(&parse("<lt><Esc>a<C-S-Tab> <c-x>").unwrap()),
+        "<lt><Esc>a<C-S-Tab><Space><C-x>"
+    );
+}
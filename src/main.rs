@@ -1,10 +1,32 @@
 mod buffer;
+mod config;
 //mod context;
 mod cursor;
 mod display;
+#[allow(dead_code)]
 mod document;
+mod editor;
+mod encoding;
+mod ex;
+mod hex;
+mod history;
+mod indent;
+mod keymap;
+mod motion;
+mod paged;
+mod pair;
+mod picker;
+#[allow(dead_code)]
 mod plane;
+mod prompt;
+mod save;
+mod search;
+mod selection;
+mod shell;
+mod state;
+mod swap;
 mod unicode;
+mod watch;
 
 fn main() {
     let mut input = std::env::args().skip(1).peekable();
@@ -0,0 +1,251 @@
+use std::ops::Range;
+
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::{cursor::Cursor, pair};
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Motion {
+    Left,
+    Down,
+    Up,
+    Right,
+    Pair,
+    First,
+    Last,
+    Line(usize),
+    Percent,
+    Offset,
+    NextHeading,
+    PreviousHeading,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Operator {
+    Delete,
+    Yank,
+    Change,
+    ShiftRight,
+    ShiftLeft,
+    ToggleCase,
+}
+
+impl Motion {
+    pub fn apply(&self, cursor: &Cursor, source: &str, count: Option<usize>) -> Cursor {
+        let lines = line_count(source);
+
+        match self {
+            Motion::Left => cursor.backward_graphemes(source, count.unwrap_or(1)),
+            Motion::Down => cursor.forward_lines(source, count.unwrap_or(1)),
+            Motion::Up => up(cursor, source, count.unwrap_or(1)),
+            Motion::Right => cursor.forward_graphemes(source, count.unwrap_or(1)),
+            Motion::Pair => match pair::find(source, cursor.z()) {
+                Some(pair) => cursor.seek(source, pair.to().start),
+                None => cursor.clone(),
+            },
+            Motion::First => line(source, count.unwrap_or(1)),
+            Motion::Last => line(source, count.unwrap_or(lines)),
+            Motion::Line(number) => line(source, *number),
+            Motion::Percent => line(source, (count.unwrap_or(100) * lines).div_ceil(100)),
+            Motion::Offset => offset(cursor, source, count.unwrap_or(1).saturating_sub(1)),
+            Motion::NextHeading => {
+                let target = (0..count.unwrap_or(1)).fold(cursor.y(), |y, _| {
+                    headings(source)
+                        .find(|heading| *heading > y)
+                        .unwrap_or(lines - 1)
+                });
+
+                line(source, target + 1)
+            }
+            Motion::PreviousHeading => {
+                let target = (0..count.unwrap_or(1)).fold(cursor.y(), |y, _| {
+                    headings(source)
+                        .take_while(|heading| *heading < y)
+                        .last()
+                        .unwrap_or(0)
+                });
+
+                line(source, target + 1)
+            }
+        }
+    }
+
+    pub fn linewise(&self) -> bool {
+        matches!(
+            self,
+            Motion::Down
+                | Motion::Up
+                | Motion::First
+                | Motion::Last
+                | Motion::Line(_)
+                | Motion::Percent
+        )
+    }
+
+    pub fn inclusive(&self) -> bool {
+        matches!(self, Motion::Pair)
+    }
+
+    pub fn relative(&self) -> bool {
+        matches!(
+            self,
+            Motion::Left
+                | Motion::Down
+                | Motion::Up
+                | Motion::Right
+                | Motion::Pair
+                | Motion::NextHeading
+                | Motion::PreviousHeading
+        )
+    }
+
+    pub fn span(&self, cursor: &Cursor, source: &str, count: Option<usize>) -> Range<usize> {
+        let target = self.apply(cursor, source, count);
+
+        let (start, end) = if target.z() < cursor.z() {
+            (target, cursor.clone())
+        } else {
+            (cursor.clone(), target)
+        };
+
+        if self.linewise() {
+            let start = source[..start.z()].rfind('\n').map_or(0, |index| index + 1);
+            let end = source[end.z()..]
+                .find('\n')
+                .map_or(source.len(), |index| end.z() + index + 1);
+
+            start..end
+        } else if self.inclusive() {
+            start.z()..end.z() + end.current(source).len()
+        } else {
+            start.z()..end.z()
+        }
+    }
+}
+
+pub fn line_count(source: &str) -> usize {
+    let source = source.strip_suffix('\n').unwrap_or(source);
+
+    source.bytes().filter(|byte| *byte == b'\n').count() + 1
+}
+
+fn line(source: &str, number: usize) -> Cursor {
+    let y = number.clamp(1, line_count(source)) - 1;
+
+    Cursor::default().forward_lines(source, y)
+}
+
+fn up(cursor: &Cursor, source: &str, count: usize) -> Cursor {
+    let start = line(source, cursor.y().saturating_sub(count) + 1).z();
+    let end = source[start..]
+        .find('\n')
+        .map_or(source.len(), |index| start + index);
+
+    let z = source[start..end]
+        .grapheme_indices(true)
+        .map(|(index, _)| start + index)
+        .take_while(|index| *index - start <= cursor.x())
+        .last()
+        .unwrap_or(start);
+
+    cursor.seek(source, z).settle(source)
+}
+
+fn headings(source: &str) -> impl Iterator<Item = usize> + '_ {
+    let mut fenced = false;
+
+    source.lines().enumerate().filter_map(move |(index, text)| {
+        if text.trim_start().starts_with("```") {
+            fenced = !fenced;
+        }
+
+        match !fenced && text.starts_with('#') {
+            true => Some(index),
+            false => None,
+        }
+    })
+}
+
+fn offset(cursor: &Cursor, source: &str, z: usize) -> Cursor {
+    let z = z.min(source.len().saturating_sub(1));
+
+    let line_start = source.as_bytes()[..z]
+        .iter()
+        .rposition(|byte| *byte == b'\n')
+        .map_or(0, |index| index + 1);
+
+    let z = source[line_start..]
+        .grapheme_indices(true)
+        .map(|(index, _)| line_start + index)
+        .take_while(|index| *index <= z)
+        .last()
+        .unwrap_or(line_start);
+
+    cursor.seek(source, z).settle(source)
+}
+
+#[test]
+fn absolute() {
+    let buffer = include_str!("../edit");
+
+    let from = Cursor::from((3, 2, 11));
+
+    assert_eq!(line_count(buffer), 11);
+
+    assert_eq!(Motion::First.apply(&from, buffer, None), (0, 0, 0).into());
+    assert_eq!(
+        Motion::First.apply(&from, buffer, Some(3)),
+        (0, 2, 8).into()
+    );
+
+    let last = Motion::Last.apply(&from, buffer, None);
+
+    assert_eq!(last.y(), 10);
+    assert_eq!(last.current(buffer), "C");
+    assert_eq!(Motion::Line(99).apply(&from, buffer, None), last);
+    assert_eq!(Motion::Line(0).apply(&from, buffer, None), (0, 0, 0).into());
+
+    assert_eq!(Motion::Percent.apply(&from, buffer, Some(50)).y(), 5);
+    assert_eq!(Motion::Percent.apply(&from, buffer, Some(100)), last);
+
+    assert_eq!(Motion::Offset.apply(&from, buffer, Some(12)), from);
+    assert_eq!(
+        Motion::Offset.apply(&from, buffer, Some(7)),
+        (5, 0, 5).into()
+    );
+    assert_eq!(
+        Motion::Offset.apply(&from, buffer, Some(8)),
+        (0, 1, 7).into()
+    );
+
+    assert_eq!(
+        Motion::NextHeading.apply(&from, buffer, None),
+        (0, 4, 35).into()
+    );
+    assert_eq!(Motion::NextHeading.apply(&from, buffer, Some(3)), last);
+    assert_eq!(
+        Motion::PreviousHeading.apply(&last, buffer, Some(2)),
+        (0, 4, 35).into()
+    );
+    assert_eq!(Motion::Up.apply(&last, buffer, Some(2)), (0, 8, 173).into());
+    assert_eq!(Motion::Up.apply(&from, buffer, Some(2)), (3, 0, 3).into());
+    assert_eq!(Motion::Up.apply(&from, buffer, Some(1)), (0, 1, 7).into());
+}
+
+#[test]
+fn spans() {
+    let buffer = include_str!("../edit");
+
+    let from = Cursor::from((2, 2, 10));
+
+    assert_eq!(
+        &buffer[Motion::Down.span(&from, buffer, Some(0))],
+        "> `Canker` but communist.\n"
+    );
+    assert_eq!(
+        &buffer[Motion::First.span(&from, buffer, None)],
+        "# Jago\n\n> `Canker` but communist.\n"
+    );
+    assert_eq!(&buffer[Motion::Right.span(&from, buffer, Some(2))], "`C");
+    assert_eq!(&buffer[Motion::Pair.span(&from, buffer, None)], "`Canker`");
+}
@@ -0,0 +1,300 @@
+use std::{
+    fs::File,
+    io,
+    os::unix::fs::FileExt,
+    sync::{
+        mpsc::{channel, Receiver, TryRecvError},
+        Arc,
+    },
+    thread,
+};
+
+const STRIDE: usize = 256;
+const BATCH: usize = 1 << 22;
+const LIMIT: usize = 1 << 16;
+const PAGE: usize = 1 << 12;
+
+pub trait Source: Send + Sync {
+    fn read_at(&self, buffer: &mut [u8], offset: usize) -> io::Result<usize>;
+}
+
+impl Source for File {
+    fn read_at(&self, buffer: &mut [u8], offset: usize) -> io::Result<usize> {
+        FileExt::read_at(self, buffer, offset as u64)
+    }
+}
+
+impl Source for Vec<u8> {
+    fn read_at(&self, buffer: &mut [u8], offset: usize) -> io::Result<usize> {
+        let available = self.get(offset..).unwrap_or_default();
+        let length = buffer.len().min(available.len());
+
+        buffer[..length].copy_from_slice(&available[..length]);
+
+        Ok(length)
+    }
+}
+
+type Bytes = Arc<dyn Source>;
+
+pub struct Paged {
+    bytes: Bytes,
+    len: usize,
+    checkpoints: Vec<usize>,
+    lines: usize,
+    receiver: Receiver<(Vec<usize>, usize)>,
+    done: bool,
+}
+
+impl Paged {
+    pub fn open(file: File) -> io::Result<Self> {
+        let len = file.metadata()?.len() as usize;
+
+        Ok(Self::new(Arc::new(file), len))
+    }
+
+    pub fn new(bytes: Bytes, len: usize) -> Self {
+        let (sender, receiver) = channel();
+
+        let source = Arc::clone(&bytes);
+
+        thread::spawn(move || {
+            let (mut offset, mut lines) = (0, 1);
+            let mut checkpoints = vec![];
+            let mut carriage = false;
+
+            let start = |next: usize, lines: &mut usize, checkpoints: &mut Vec<usize>| {
+                if next >= len {
+                    return;
+                }
+
+                if lines.is_multiple_of(STRIDE) {
+                    checkpoints.push(next);
+                }
+
+                *lines += 1;
+            };
+
+            loop {
+                let chunk = read(&*source, len, offset, BATCH);
+
+                if chunk.is_empty() {
+                    break;
+                }
+
+                for (index, byte) in chunk.iter().enumerate() {
+                    let at = offset + index;
+
+                    if std::mem::take(&mut carriage) {
+                        if *byte == b'\n' {
+                            start(at + 1, &mut lines, &mut checkpoints);
+
+                            continue;
+                        }
+
+                        start(at, &mut lines, &mut checkpoints);
+                    }
+
+                    match byte {
+                        b'\n' => start(at + 1, &mut lines, &mut checkpoints),
+                        b'\r' => carriage = true,
+                        _ => {}
+                    };
+                }
+
+                offset += chunk.len();
+
+                if sender
+                    .send((std::mem::take(&mut checkpoints), lines))
+                    .is_err()
+                {
+                    return;
+                }
+            }
+
+            if carriage {
+                start(offset, &mut lines, &mut checkpoints);
+            }
+
+            let _ = sender.send((checkpoints, lines));
+        });
+
+        Self {
+            bytes,
+            len,
+            checkpoints: vec![0],
+            lines: 1,
+            receiver,
+            done: false,
+        }
+    }
+
+    pub fn poll(&mut self) -> bool {
+        let before = self.lines;
+
+        while !self.done {
+            match self.receiver.try_recv() {
+                Ok((checkpoints, lines)) => {
+                    self.checkpoints.extend(checkpoints);
+                    self.lines = lines;
+                }
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => self.done = true,
+            }
+        }
+
+        self.lines != before
+    }
+
+    pub fn done(&self) -> bool {
+        self.done
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn lines(&self) -> usize {
+        self.lines
+    }
+
+    pub fn offset(&self, line: usize) -> usize {
+        let line = line.min(self.lines - 1);
+
+        (0..line % STRIDE).fold(self.checkpoints[line / STRIDE], |start, _| {
+            self.next_line(start).unwrap_or(start)
+        })
+    }
+
+    pub fn view(&self, top: usize, count: usize) -> String {
+        let mut view = String::new();
+        let mut start = Some(self.offset(top));
+
+        for _ in top..(top + count).min(self.lines) {
+            let line = match start {
+                Some(line) => line,
+                None => break,
+            };
+
+            start = self.next_line(line);
+
+            let bytes = self.read(line, start.map_or(LIMIT, |next| next - line).min(LIMIT));
+
+            let end = bytes
+                .iter()
+                .position(|byte| matches!(byte, b'\n' | b'\r'))
+                .unwrap_or(bytes.len());
+
+            view.push_str(&String::from_utf8_lossy(&bytes[..end]));
+            view.push('\n');
+        }
+
+        view
+    }
+
+    fn read(&self, start: usize, length: usize) -> Vec<u8> {
+        read(&*self.bytes, self.len, start, length)
+    }
+
+    fn next_line(&self, start: usize) -> Option<usize> {
+        let mut offset = start;
+
+        loop {
+            let bytes = self.read(offset, PAGE);
+
+            let index = match bytes.iter().position(|byte| matches!(byte, b'\n' | b'\r')) {
+                Some(index) => index,
+                None if bytes.is_empty() => return None,
+                None => {
+                    offset += bytes.len();
+
+                    continue;
+                }
+            };
+
+            let at = offset + index;
+
+            let next = match (bytes[index], bytes.get(index + 1)) {
+                (b'\r', Some(b'\n')) => at + 2,
+                (b'\r', None) if self.read(at + 1, 1) == b"\n" => at + 2,
+                _ => at + 1,
+            };
+
+            return (next < self.len).then_some(next);
+        }
+    }
+}
+
+fn read(source: &dyn Source, len: usize, start: usize, length: usize) -> Vec<u8> {
+    let mut bytes = vec![0; length.min(len.saturating_sub(start))];
+    let mut filled = 0;
+
+    while filled < bytes.len() {
+        match source.read_at(&mut bytes[filled..], start + filled) {
+            Ok(0) => break,
+            Ok(count) => filled += count,
+            Err(error) if error.kind() == io::ErrorKind::Interrupted => {}
+            Err(_) => break,
+        }
+    }
+
+    bytes.truncate(filled);
+
+    bytes
+}
+
+#[test]
+fn index() {
+    let text = (0..STRIDE * 2 + 5)
+        .map(|line| format!("line {}\r\n", line))
+        .collect::<String>();
+
+    let mut paged = Paged::new(Arc::new(text.clone().into_bytes()), text.len());
+
+    while !paged.done() {
+        paged.poll();
+    }
+
+    assert_eq!(paged.lines(), STRIDE * 2 + 5);
+    assert_eq!(paged.len(), text.len());
+    assert_eq!(paged.offset(0), 0);
+    assert_eq!(
+        paged.offset(STRIDE + 3),
+        text.find(&format!("line {}\r", STRIDE + 3)).unwrap()
+    );
+    assert_eq!(
+        paged.view(STRIDE * 2 + 3, 10),
+        format!("line {}\nline {}\n", STRIDE * 2 + 3, STRIDE * 2 + 4)
+    );
+
+    let mut paged = Paged::new(Arc::new(b"one\rtwo\n\nthree".to_vec()), 15);
+
+    while !paged.done() {
+        paged.poll();
+    }
+
+    assert_eq!(paged.lines(), 4);
+    assert_eq!(paged.view(1, 3), "two\n\nthree\n");
+
+    let mut truncated = Paged::new(Arc::new(b"one\ntwo\n".to_vec()), 64);
+
+    while !truncated.done() {
+        truncated.poll();
+    }
+
+    assert_eq!(truncated.lines(), 3);
+    assert_eq!(truncated.view(0, 3), "one\ntwo\n\n");
+
+    let long = format!("{}\r\nend\n", "x".repeat(PAGE * 2 - 1));
+    let mut paged = Paged::new(Arc::new(long.clone().into_bytes()), long.len());
+
+    while !paged.done() {
+        paged.poll();
+    }
+
+    assert_eq!(paged.offset(1), PAGE * 2 + 1);
+    assert_eq!(
+        paged.view(0, 2),
+        format!("{}\nend\n", "x".repeat(PAGE * 2 - 1))
+    );
+}
@@ -0,0 +1,368 @@
+use std::ops::Range;
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Pair {
+    from: Range<usize>,
+    to: Range<usize>,
+}
+
+impl Pair {
+    pub fn from(&self) -> Range<usize> {
+        self.from.clone()
+    }
+
+    pub fn to(&self) -> Range<usize> {
+        self.to.clone()
+    }
+}
+
+pub fn find(buffer: &str, z: usize) -> Option<Pair> {
+    let bytes = buffer.as_bytes();
+
+    let line_end = bytes[z.min(bytes.len())..]
+        .iter()
+        .position(|byte| *byte == b'\n')
+        .map_or(bytes.len(), |index| z + index);
+
+    (z..line_end)
+        .filter(|index| is_delimiter(bytes[*index]))
+        .find_map(|index| at(buffer, index))
+}
+
+pub fn at(buffer: &str, z: usize) -> Option<Pair> {
+    let bytes = buffer.as_bytes();
+
+    match bytes.get(z)? {
+        b'(' | b')' | b'[' | b']' | b'{' | b'}' => brackets(bytes, z),
+        b'`' | b'~' => fence(buffer, z).or_else(|| code(buffer, z)),
+        b'*' | b'_' => emphasis(buffer, z),
+        _ => None,
+    }
+}
+
+fn is_delimiter(byte: u8) -> bool {
+    matches!(
+        byte,
+        b'(' | b')' | b'[' | b']' | b'{' | b'}' | b'`' | b'~' | b'*' | b'_'
+    )
+}
+
+fn brackets(bytes: &[u8], z: usize) -> Option<Pair> {
+    let (open, close) = match bytes[z] {
+        b'(' | b')' => (b'(', b')'),
+        b'[' | b']' => (b'[', b']'),
+        _ => (b'{', b'}'),
+    };
+
+    let mut depth = 0usize;
+
+    let to = if bytes[z] == open {
+        bytes[z..]
+            .iter()
+            .position(|byte| {
+                if *byte == open {
+                    depth += 1;
+                } else if *byte == close {
+                    depth -= 1;
+                }
+
+                depth == 0
+            })
+            .map(|index| z + index)
+    } else {
+        bytes[..=z].iter().rposition(|byte| {
+            if *byte == close {
+                depth += 1;
+            } else if *byte == open {
+                depth -= 1;
+            }
+
+            depth == 0
+        })
+    }?;
+
+    Some(Pair {
+        from: z..z + 1,
+        to: to..to + 1,
+    })
+}
+
+fn run(bytes: &[u8], z: usize) -> Range<usize> {
+    let delimiter = bytes[z];
+
+    let start = bytes[..z]
+        .iter()
+        .rposition(|byte| *byte != delimiter)
+        .map_or(0, |index| index + 1);
+
+    let end = bytes[z..]
+        .iter()
+        .position(|byte| *byte != delimiter)
+        .map_or(bytes.len(), |index| z + index);
+
+    start..end
+}
+
+fn runs(bytes: &[u8], span: Range<usize>, delimiter: u8) -> Vec<Range<usize>> {
+    let mut runs = vec![];
+    let mut index = span.start;
+
+    while index < span.end {
+        if bytes[index] == delimiter {
+            let run = run(bytes, index);
+            let run = run.start.max(span.start)..run.end.min(span.end);
+
+            index = run.end;
+            runs.push(run);
+        } else {
+            index += 1;
+        }
+    }
+
+    runs
+}
+
+fn paragraph(buffer: &str, z: usize) -> Range<usize> {
+    let start = buffer[..z].rfind("\n\n").map_or(0, |index| index + 2);
+    let end = buffer[z..]
+        .find("\n\n")
+        .map_or(buffer.len(), |index| z + index);
+
+    start..end
+}
+
+fn code_spans(bytes: &[u8], span: Range<usize>) -> Vec<(Range<usize>, Range<usize>)> {
+    let runs = runs(bytes, span, b'`');
+
+    let mut spans = vec![];
+    let mut rest = &runs[..];
+
+    while let Some((open, tail)) = rest.split_first() {
+        match tail.iter().position(|close| close.len() == open.len()) {
+            Some(index) => {
+                spans.push((open.clone(), tail[index].clone()));
+                rest = &tail[index + 1..];
+            }
+            None => rest = tail,
+        }
+    }
+
+    spans
+}
+
+fn code(buffer: &str, z: usize) -> Option<Pair> {
+    let bytes = buffer.as_bytes();
+
+    if bytes[z] != b'`' {
+        return None;
+    }
+
+    code_spans(bytes, paragraph(buffer, z))
+        .into_iter()
+        .find_map(|(open, close)| {
+            if open.contains(&z) {
+                Some(Pair {
+                    from: open,
+                    to: close,
+                })
+            } else if close.contains(&z) {
+                Some(Pair {
+                    from: close,
+                    to: open,
+                })
+            } else {
+                None
+            }
+        })
+}
+
+fn emphasis(buffer: &str, z: usize) -> Option<Pair> {
+    let bytes = buffer.as_bytes();
+    let delimiter = bytes[z];
+    let span = paragraph(buffer, z);
+
+    let code_spans = code_spans(bytes, span.clone());
+
+    let literal = |index: usize| {
+        code_spans
+            .iter()
+            .any(|(open, close)| open.start <= index && index < close.end)
+    };
+
+    let mut openers: Vec<Range<usize>> = vec![];
+
+    for run in runs(bytes, span, delimiter) {
+        if literal(run.start) {
+            continue;
+        }
+
+        let before = buffer[..run.start].chars().next_back();
+        let after = buffer[run.end..].chars().next();
+
+        let mut can_open = after.is_some_and(|after| !after.is_whitespace());
+        let mut can_close = before.is_some_and(|before| !before.is_whitespace());
+
+        if delimiter == b'_'
+            && before.is_some_and(char::is_alphanumeric)
+            && after.is_some_and(char::is_alphanumeric)
+        {
+            can_open = false;
+            can_close = false;
+        }
+
+        let opener = if can_close {
+            openers.iter().rposition(|open| open.len() == run.len())
+        } else {
+            None
+        };
+
+        match opener {
+            Some(index) => {
+                let open = openers.remove(index);
+                openers.truncate(index);
+
+                if open.contains(&z) {
+                    return Some(Pair {
+                        from: open,
+                        to: run,
+                    });
+                } else if run.contains(&z) {
+                    return Some(Pair {
+                        from: run,
+                        to: open,
+                    });
+                }
+            }
+            None if can_open => openers.push(run),
+            None => {}
+        }
+    }
+
+    None
+}
+
+fn fence_of(line: &str) -> Option<Range<usize>> {
+    let indent = line.len() - line.trim_start_matches(' ').len();
+
+    if indent > 3 {
+        return None;
+    }
+
+    let bytes = &line.as_bytes()[indent..];
+    let delimiter = *bytes.first().filter(|byte| matches!(byte, b'`' | b'~'))?;
+    let length = bytes.iter().take_while(|byte| **byte == delimiter).count();
+
+    if length >= 3 {
+        Some(indent..indent + length)
+    } else {
+        None
+    }
+}
+
+fn fence(buffer: &str, z: usize) -> Option<Pair> {
+    let line_start = buffer[..z].rfind('\n').map_or(0, |index| index + 1);
+
+    let here = {
+        let line = buffer[line_start..].split('\n').next().unwrap_or("");
+        let run = fence_of(line)?;
+
+        line_start + run.start..line_start + run.end
+    };
+
+    if !here.contains(&z) {
+        return None;
+    }
+
+    let mut open: Option<Range<usize>> = None;
+    let mut line_start = 0;
+
+    for line in buffer.split('\n') {
+        let offset = line_start;
+
+        line_start += line.len() + 1;
+
+        let run = match fence_of(line) {
+            Some(run) => run,
+            None => continue,
+        };
+
+        let closes = |opener: &Range<usize>| {
+            buffer.as_bytes()[opener.start] == line.as_bytes()[run.start]
+                && run.len() >= opener.len()
+                && line[run.end..].trim().is_empty()
+        };
+
+        let run = offset + run.start..offset + run.end;
+
+        match open.take() {
+            Some(opener) if closes(&opener) => {
+                if opener == here {
+                    return Some(Pair {
+                        from: opener,
+                        to: run,
+                    });
+                } else if run == here {
+                    return Some(Pair {
+                        from: run,
+                        to: opener,
+                    });
+                }
+            }
+            Some(opener) => open = Some(opener),
+            None if run.start > here.start => return None,
+            None => open = Some(run),
+        }
+    }
+
+    None
+}
+
+#[test]
+fn brackets_and_code_spans() {
+    let buffer = include_str!("../edit");
+
+    let quote = buffer.find('`').unwrap();
+
+    assert_eq!(
+        find(buffer, quote),
+        Some(Pair {
+            from: quote..quote + 1,
+            to: quote + 7..quote + 8,
+        })
+    );
+    assert_eq!(
+        find(buffer, quote + 7).map(|pair| pair.to()),
+        Some(quote..quote + 1)
+    );
+    assert_eq!(
+        find(buffer, quote - 2).map(|pair| pair.to()),
+        Some(quote + 7..quote + 8)
+    );
+    assert_eq!(find(buffer, 0), None);
+
+    let buffer = "f(a, [b], {c: (d)})";
+
+    assert_eq!(find(buffer, 1).map(|pair| pair.to()), Some(18..19));
+    assert_eq!(find(buffer, 18).map(|pair| pair.to()), Some(1..2));
+    assert_eq!(find(buffer, 5).map(|pair| pair.to()), Some(7..8));
+    assert_eq!(find(buffer, 14).map(|pair| pair.to()), Some(16..17));
+    assert_eq!(find(buffer, 0).map(|pair| pair.to()), Some(18..19));
+}
+
+#[test]
+fn markdown_delimiters() {
+    let buffer = "a **strong** and _em_ in snake_case_name\n\n``code ` span``\n";
+
+    assert_eq!(find(buffer, 2).map(|pair| pair.to()), Some(10..12));
+    assert_eq!(find(buffer, 11).map(|pair| pair.to()), Some(2..4));
+    assert_eq!(find(buffer, 17).map(|pair| pair.to()), Some(20..21));
+    assert_eq!(at(buffer, 30), None);
+    assert_eq!(find(buffer, 42).map(|pair| pair.to()), Some(55..57));
+
+    let buffer = "text\n```rust\nlet (a) = b;\n```\n\n~~~\nopen\n";
+
+    assert_eq!(find(buffer, 5).map(|pair| pair.to()), Some(26..29));
+    assert_eq!(find(buffer, 27).map(|pair| pair.to()), Some(5..8));
+    assert_eq!(find(buffer, 17).map(|pair| pair.to()), Some(19..20));
+    assert_eq!(find(buffer, 31), None);
+}
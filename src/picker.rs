@@ -0,0 +1,377 @@
+use std::{
+    cmp::Ordering,
+    fs::File,
+    io::Read,
+    ops::Range,
+    path::{Path, PathBuf},
+    sync::mpsc::{channel, Receiver, TryRecvError},
+    thread,
+};
+
+use itertools::Itertools;
+
+pub struct Picker {
+    root: PathBuf,
+    paths: Vec<PathBuf>,
+    receiver: Receiver<PathBuf>,
+    done: bool,
+    query: String,
+    matches: Vec<(i64, usize)>,
+    selected: usize,
+    preview: String,
+    previewed: Option<usize>,
+    size: (u16, u16),
+}
+
+pub enum Pick {
+    Pending,
+    Cancel,
+    Open(PathBuf),
+}
+
+impl Picker {
+    pub fn new(root: &Path, size: (u16, u16)) -> Self {
+        let (sender, receiver) = channel();
+
+        let walk = ignore::WalkBuilder::new(root).require_git(false).build();
+        let base = root.to_path_buf();
+
+        thread::spawn(move || {
+            for entry in walk.filter_map(Result::ok) {
+                if !entry.file_type().is_some_and(|kind| kind.is_file()) {
+                    continue;
+                }
+
+                let path = match entry.path().strip_prefix(&base) {
+                    Ok(path) => path.to_path_buf(),
+                    Err(_) => continue,
+                };
+
+                if sender.send(path).is_err() {
+                    return;
+                }
+            }
+        });
+
+        Self {
+            root: root.into(),
+            paths: vec![],
+            receiver,
+            done: false,
+            query: String::new(),
+            matches: vec![],
+            selected: 0,
+            preview: String::new(),
+            previewed: None,
+            size,
+        }
+    }
+
+    pub fn poll(&mut self) -> bool {
+        let before = (self.paths.len(), self.done);
+
+        while !self.done {
+            match self.receiver.try_recv() {
+                Ok(path) => self.paths.push(path),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => self.done = true,
+            }
+        }
+
+        if self.paths.len() == before.0 {
+            return self.done != before.1;
+        }
+
+        let selected = self.matches.get(self.selected).copied();
+        let found = self.score(before.0..self.paths.len());
+        let paths = &self.paths;
+
+        self.matches = std::mem::take(&mut self.matches)
+            .into_iter()
+            .merge_by(found, |a, b| order(paths, a, b) != Ordering::Greater)
+            .collect();
+
+        self.selected = selected
+            .and_then(|selected| self.matches.iter().position(|found| *found == selected))
+            .unwrap_or(0);
+        self.load_preview();
+
+        true
+    }
+
+    pub fn selected(&self) -> Option<&Path> {
+        self.matches
+            .get(self.selected)
+            .map(|(_, index)| self.paths[*index].as_path())
+    }
+
+    fn rank(&mut self) {
+        self.matches = self.score(0..self.paths.len());
+        self.selected = 0;
+        self.load_preview();
+    }
+
+    fn score(&self, indices: Range<usize>) -> Vec<(i64, usize)> {
+        let mut scored = indices
+            .filter_map(|index| {
+                score(&self.query, &self.paths[index].to_string_lossy()).map(|score| (score, index))
+            })
+            .collect::<Vec<_>>();
+
+        scored.sort_by(|a, b| order(&self.paths, a, b));
+
+        scored
+    }
+
+    fn select(&mut self, selected: usize) {
+        if selected < self.matches.len() {
+            self.selected = selected;
+            self.load_preview();
+        }
+    }
+
+    fn load_preview(&mut self) {
+        let previewed = self.matches.get(self.selected).map(|(_, index)| *index);
+
+        if std::mem::replace(&mut self.previewed, previewed) == previewed {
+            return;
+        }
+
+        let mut bytes = vec![];
+
+        let path = match self.selected() {
+            Some(path) => self.root.join(path),
+            None => {
+                self.preview.clear();
+                return;
+            }
+        };
+
+        let read = File::open(path).and_then(|file| {
+            file.take(self.size.0 as u64 * self.size.1 as u64)
+                .read_to_end(&mut bytes)
+        });
+
+        self.preview = match read {
+            Ok(_) if bytes.contains(&0) => "(binary)".into(),
+            Ok(_) => String::from_utf8_lossy(&bytes).into(),
+            Err(error) => error.to_string(),
+        };
+    }
+}
+
+fn order(paths: &[PathBuf], a: &(i64, usize), b: &(i64, usize)) -> Ordering {
+    b.0.cmp(&a.0).then_with(|| paths[a.1].cmp(&paths[b.1]))
+}
+
+pub fn score(query: &str, candidate: &str) -> Option<i64> {
+    let smart_case = query.chars().any(char::is_uppercase);
+
+    let fold = |c: char| {
+        if smart_case {
+            c
+        } else {
+            c.to_ascii_lowercase()
+        }
+    };
+
+    let mut query = query.chars().map(fold).peekable();
+
+    let mut score = 0;
+    let mut previous: Option<char> = None;
+    let mut consecutive = false;
+
+    for c in candidate.chars() {
+        let want = match query.peek() {
+            Some(want) => *want,
+            None => break,
+        };
+
+        if fold(c) == want {
+            score += 1;
+
+            if consecutive {
+                score += 8;
+            }
+
+            match previous {
+                None => score += 12,
+                Some('/' | '_' | '-' | '.' | ' ') => score += 10,
+                Some(previous) if previous.is_lowercase() && c.is_uppercase() => score += 8,
+                _ => {}
+            }
+
+            consecutive = true;
+            query.next();
+        } else {
+            consecutive = false;
+        }
+
+        previous = Some(c);
+    }
+
+    if query.peek().is_some() {
+        return None;
+    }
+
+    Some(score * 16 - candidate.len() as i64)
+}
+
+#[test]
+fn scores() {
+    assert_eq!(score("", "src/main.rs"), Some(-11));
+    assert_eq!(score("xyz", "src/main.rs"), None);
+    assert!(score("main", "src/main.rs") > score("main", "src/domain.rs"));
+    assert!(score("buf", "src/buffer.rs") > score("buf", "src/bin/useful.rs"));
+    assert!(score("Buf", "src/buffer.rs").is_none());
+    assert!(score("cur", "src/cursor.rs") > score("cur", "src/cursor1.rs"));
+}
+
+#[test]
+fn walk() {
+    let mut picker = Picker::new(Path::new(env!("CARGO_MANIFEST_DIR")), (80, 24));
+
+    while !picker.done {
+        picker.poll();
+    }
+
+    assert!(picker.paths.iter().all(|path| !path.starts_with("target")));
+    assert_eq!(picker.matches, picker.score(0..picker.paths.len()));
+    assert_eq!(clip("日本語.md", 5), "日本");
+
+    picker.query = "srcpick".into();
+    picker.rank();
+
+    assert_eq!(picker.selected(), Some(Path::new("src/picker.rs")));
+    assert!(picker.preview.starts_with("use std::{"));
+}
+
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+
+impl Picker {
+    pub fn handle(&mut self, event: &Event) -> Pick {
+        match event {
+            Event::Key(KeyEvent {
+                code: KeyCode::Esc,
+                modifiers: _,
+            }) => return Pick::Cancel,
+            Event::Key(KeyEvent {
+                code: KeyCode::Enter,
+                modifiers: _,
+            }) => {
+                if let Some(path) = self.selected() {
+                    return Pick::Open(self.root.join(path));
+                }
+            }
+            Event::Key(KeyEvent {
+                code: KeyCode::Up,
+                modifiers: _,
+            })
+            | Event::Key(KeyEvent {
+                code: KeyCode::Char('p'),
+                modifiers: KeyModifiers::CONTROL,
+            }) => {
+                self.select(self.selected.saturating_sub(1));
+            }
+            Event::Key(KeyEvent {
+                code: KeyCode::Down,
+                modifiers: _,
+            })
+            | Event::Key(KeyEvent {
+                code: KeyCode::Char('n'),
+                modifiers: KeyModifiers::CONTROL,
+            }) => {
+                self.select(self.selected + 1);
+            }
+            Event::Key(KeyEvent {
+                code: KeyCode::Backspace,
+                modifiers: _,
+            }) if !self.query.is_empty() => {
+                self.query.pop();
+                self.rank();
+            }
+            Event::Key(KeyEvent {
+                code: KeyCode::Char(code),
+                modifiers: _,
+            }) => {
+                self.query.push(*code);
+                self.rank();
+            }
+            Event::Resize(width, height) => {
+                self.size = (*width, *height);
+                self.previewed = None;
+                self.load_preview();
+            }
+            _ => {}
+        };
+
+        Pick::Pending
+    }
+}
+
+use crossterm::{
+    cursor::MoveTo,
+    style::{Attribute, Color, Print, SetAttribute, SetForegroundColor},
+    terminal::{Clear, ClearType},
+    Command,
+};
+use unicode_width::UnicodeWidthChar;
+
+impl Command for Picker {
+    fn write_ansi(&self, out: &mut impl std::fmt::Write) -> std::fmt::Result {
+        let (width, height) = (self.size.0 as usize, self.size.1 as usize);
+        let half = width / 2;
+        let rows = height.saturating_sub(1);
+
+        Clear(ClearType::All).write_ansi(out)?;
+
+        let first = self.selected.saturating_sub(rows.saturating_sub(1));
+
+        for (row, (_, index)) in self.matches.iter().skip(first).take(rows).enumerate() {
+            let path = self.paths[*index].to_string_lossy();
+
+            MoveTo(0, row as u16).write_ansi(out)?;
+
+            if first + row == self.selected {
+                SetAttribute(Attribute::Reverse).write_ansi(out)?;
+                Print(clip(&path, half.saturating_sub(1))).write_ansi(out)?;
+                SetAttribute(Attribute::NoReverse).write_ansi(out)?;
+            } else {
+                Print(clip(&path, half.saturating_sub(1))).write_ansi(out)?;
+            }
+        }
+
+        SetForegroundColor(Color::DarkGrey).write_ansi(out)?;
+
+        for (row, line) in self.preview.lines().take(rows).enumerate() {
+            MoveTo(half as u16, row as u16).write_ansi(out)?;
+            Print(clip(line, width - half)).write_ansi(out)?;
+        }
+
+        SetForegroundColor(Color::Reset).write_ansi(out)?;
+        MoveTo(0, rows as u16).write_ansi(out)?;
+        Print(format!(
+            "{}/{}{} > {}",
+            self.matches.len(),
+            self.paths.len(),
+            if self.done { "" } else { "+" },
+            self.query
+        ))
+        .write_ansi(out)?;
+
+        Ok(())
+    }
+}
+
+fn clip(line: &str, width: usize) -> String {
+    let mut used = 0;
+
+    line.chars()
+        .filter(|c| !c.is_control())
+        .take_while(|c| {
+            used += c.width().unwrap_or(0);
+
+            used <= width
+        })
+        .collect()
+}
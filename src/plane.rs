@@ -9,7 +9,7 @@ struct Point {
 
 #[test]
 fn test_plane() {
-    let input = b"# Jago
+    let _input = b"# Jago
 
 > `Canker` but communist.
 
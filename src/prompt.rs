@@ -0,0 +1,347 @@
+use std::{fs, path::PathBuf};
+
+use crate::config;
+
+const LIMIT: usize = 200;
+
+#[derive(Default)]
+pub struct Prompt {
+    input: Option<String>,
+    position: usize,
+    entries: Vec<String>,
+    recall: Option<usize>,
+    draft: String,
+    completion: Option<Completion>,
+    file: Option<PathBuf>,
+}
+
+struct Completion {
+    start: usize,
+    candidates: Vec<String>,
+    selected: usize,
+}
+
+pub enum Input {
+    Pending,
+    Cancel,
+    Submit(String),
+    Complete,
+}
+
+pub fn history() -> Option<PathBuf> {
+    config::directory("XDG_STATE_HOME", ".local/state").map(|directory| directory.join("history"))
+}
+
+impl Prompt {
+    pub fn load(file: PathBuf) -> Self {
+        let entries = fs::read_to_string(&file)
+            .map(|source| source.lines().map(String::from).collect())
+            .unwrap_or_default();
+
+        Self {
+            entries,
+            file: Some(file),
+            ..Default::default()
+        }
+    }
+
+    pub fn open(&mut self, input: impl Into<String>) {
+        let input = input.into();
+
+        self.position = input.len();
+        self.input = Some(input);
+        self.recall = None;
+        self.completion = None;
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.input.is_some()
+    }
+
+    pub fn input(&self) -> &str {
+        self.input.as_deref().unwrap_or("")
+    }
+
+    pub fn before(&self) -> &str {
+        &self.input()[..self.position]
+    }
+
+    pub fn candidates(&self) -> Option<(&[String], usize)> {
+        self.completion
+            .as_ref()
+            .filter(|completion| completion.candidates.len() > 1)
+            .map(|completion| (&completion.candidates[..], completion.selected))
+    }
+
+    pub fn complete(&mut self, start: usize, candidates: Vec<String>) {
+        if candidates.is_empty() {
+            return;
+        }
+
+        self.completion = Some(Completion {
+            start,
+            candidates,
+            selected: 0,
+        });
+
+        self.apply();
+    }
+
+    fn cycle(&mut self, reverse: bool) {
+        if let Some(completion) = self.completion.as_mut() {
+            let count = completion.candidates.len();
+
+            completion.selected = match reverse {
+                true => (completion.selected + count - 1) % count,
+                false => (completion.selected + 1) % count,
+            };
+        }
+
+        self.apply();
+    }
+
+    fn apply(&mut self) {
+        let (input, completion) = match (self.input.as_mut(), &self.completion) {
+            (Some(input), Some(completion)) => (input, completion),
+            _ => return,
+        };
+
+        let candidate = &completion.candidates[completion.selected];
+
+        input.replace_range(completion.start..self.position, candidate);
+
+        self.position = completion.start + candidate.len();
+    }
+
+    fn remember(&mut self, entry: &str) {
+        if entry.is_empty() {
+            return;
+        }
+
+        self.entries.retain(|existing| existing != entry);
+        self.entries.push(entry.into());
+
+        if self.entries.len() > LIMIT {
+            self.entries.drain(..self.entries.len() - LIMIT);
+        }
+
+        if let Some(file) = &self.file {
+            if let Some(directory) = file.parent() {
+                let _ = fs::create_dir_all(directory);
+            }
+
+            let _ = fs::write(file, self.entries.join("\n") + "\n");
+        }
+    }
+
+    fn recall(&mut self, older: bool) {
+        let input = match self.input.as_mut() {
+            Some(input) => input,
+            None => return,
+        };
+
+        if self.recall.is_none() {
+            self.draft = input.clone();
+        }
+
+        let draft = &self.draft;
+        let matching = |index: &usize| self.entries[*index].starts_with(draft.as_str());
+
+        let next = match (older, self.recall) {
+            (true, None) => (0..self.entries.len()).rev().find(matching),
+            (true, Some(recall)) => (0..recall).rev().find(matching),
+            (false, Some(recall)) => (recall + 1..self.entries.len()).find(matching),
+            (false, None) => return,
+        };
+
+        match (next, older) {
+            (Some(index), _) => {
+                *input = self.entries[index].clone();
+                self.recall = Some(index);
+            }
+            (None, false) => {
+                *input = self.draft.clone();
+                self.recall = None;
+            }
+            (None, true) => {}
+        };
+
+        self.position = input.len();
+    }
+}
+
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::keymap::Action;
+
+impl Prompt {
+    pub fn perform(&mut self, action: Action) -> Input {
+        if !matches!(action, Action::Complete | Action::CompleteBack) {
+            self.completion = None;
+        }
+
+        let input = match self.input.as_mut() {
+            Some(input) => input,
+            None => return Input::Cancel,
+        };
+
+        match action {
+            Action::Submit => {
+                let input = self.input.take().unwrap_or_default();
+
+                self.remember(input.trim());
+
+                return Input::Submit(input);
+            }
+            Action::Cancel => {
+                self.input = None;
+
+                return Input::Cancel;
+            }
+            Action::Backspace if input.is_empty() => {
+                self.input = None;
+
+                return Input::Cancel;
+            }
+            Action::Backspace => {
+                if let Some((index, _)) = input[..self.position].grapheme_indices(true).next_back()
+                {
+                    input.replace_range(index..self.position, "");
+                    self.position = index;
+                }
+            }
+            Action::Remove => {
+                if let Some(grapheme) = input[self.position..].graphemes(true).next() {
+                    input.replace_range(self.position..self.position + grapheme.len(), "");
+                }
+            }
+            Action::Left => {
+                if let Some((index, _)) = input[..self.position].grapheme_indices(true).next_back()
+                {
+                    self.position = index;
+                }
+            }
+            Action::Right => {
+                if let Some(grapheme) = input[self.position..].graphemes(true).next() {
+                    self.position += grapheme.len();
+                }
+            }
+            Action::Home => self.position = 0,
+            Action::End => self.position = input.len(),
+            Action::Older => self.recall(true),
+            Action::Newer => self.recall(false),
+            Action::Complete | Action::CompleteBack => match self.completion {
+                Some(_) => self.cycle(action == Action::CompleteBack),
+                None => return Input::Complete,
+            },
+            _ => {}
+        };
+
+        Input::Pending
+    }
+
+    pub fn insert(&mut self, character: char) {
+        self.completion = None;
+
+        if let Some(input) = self.input.as_mut() {
+            input.insert(self.position, character);
+            self.position += character.len_utf8();
+            self.recall = None;
+        }
+    }
+}
+
+use crossterm::{
+    cursor::MoveTo,
+    style::{Attribute, Print, SetAttribute},
+    terminal::{Clear, ClearType},
+    Command,
+};
+
+impl Command for Prompt {
+    fn write_ansi(&self, out: &mut impl std::fmt::Write) -> std::fmt::Result {
+        let input = match &self.input {
+            Some(input) => input,
+            None => return Ok(()),
+        };
+
+        let (_, rows) = crossterm::terminal::size().unwrap_or((80, 24));
+        let row = rows.saturating_sub(1);
+
+        if let Some((candidates, selected)) = self.candidates() {
+            MoveTo(0, row.saturating_sub(1)).write_ansi(out)?;
+            Clear(ClearType::CurrentLine).write_ansi(out)?;
+
+            for (index, candidate) in candidates.iter().enumerate() {
+                if index == selected {
+                    SetAttribute(Attribute::Reverse).write_ansi(out)?;
+                    Print(candidate).write_ansi(out)?;
+                    SetAttribute(Attribute::NoReverse).write_ansi(out)?;
+                } else {
+                    Print(candidate).write_ansi(out)?;
+                }
+
+                Print("  ").write_ansi(out)?;
+            }
+        }
+
+        MoveTo(0, row).write_ansi(out)?;
+        Clear(ClearType::CurrentLine).write_ansi(out)?;
+        Print(format!(":{}", input)).write_ansi(out)?;
+
+        let column = 1 + input[..self.position].graphemes(true).count();
+
+        MoveTo(column as u16, row).write_ansi(out)?;
+
+        Ok(())
+    }
+}
+
+#[test]
+fn editing() {
+    let mut prompt = Prompt::default();
+
+    macro_rules! perform {
+        ($($action:ident),*) => {
+            vec![$(prompt.perform(Action::$action)),*]
+        };
+    }
+
+    for entry in ["e one", "bn", "e two"] {
+        prompt.open(entry);
+        perform!(Submit);
+    }
+
+    prompt.open("");
+    perform!(Older, Older);
+    assert_eq!(prompt.input(), "bn");
+
+    perform!(Newer, Newer);
+    assert_eq!(prompt.input(), "");
+
+    prompt.insert('e');
+    perform!(Older, Older);
+    assert_eq!(prompt.input(), "e one");
+
+    perform!(Older);
+    assert_eq!(prompt.input(), "e one");
+
+    perform!(Left, Left, Backspace);
+    prompt.insert('_');
+    assert_eq!(prompt.input(), "e _ne");
+
+    assert!(matches!(perform!(End, Complete)[1], Input::Complete));
+
+    prompt.complete(2, vec!["src/".into(), "edit".into()]);
+    assert_eq!(prompt.input(), "e src/");
+
+    perform!(Complete);
+    assert_eq!(prompt.input(), "e edit");
+
+    perform!(CompleteBack);
+    assert_eq!(prompt.input(), "e src/");
+
+    assert!(matches!(&perform!(Submit)[0], Input::Submit(input) if input == "e src/"));
+    assert_eq!(prompt.entries, ["e one", "bn", "e two", "e src/"]);
+    assert!(!prompt.is_open());
+}
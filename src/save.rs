@@ -0,0 +1,227 @@
+use std::{
+    fs::{self, File},
+    io::{self, Write},
+    os::unix::fs::{chown, MetadataExt, OpenOptionsExt},
+    path::{Path, PathBuf},
+    process,
+    time::SystemTime,
+};
+
+use crate::config;
+
+const LINKS: usize = 40;
+
+const DATED: usize = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Backup {
+    Off,
+    Single,
+    Dated,
+}
+
+pub fn write(path: &Path, bytes: &[u8], backup: Backup) -> io::Result<()> {
+    let target = resolve(path)?;
+
+    let metadata = match fs::metadata(&target) {
+        Ok(metadata) => Some(metadata),
+        Err(error) if error.kind() == io::ErrorKind::NotFound => None,
+        Err(error) => return Err(error),
+    };
+
+    if let Some(copy) = metadata.as_ref().and_then(|_| location(&target, backup)) {
+        if let Some(directory) = copy.parent() {
+            fs::create_dir_all(directory)?;
+        }
+
+        fs::copy(&target, &copy)?;
+
+        if backup == Backup::Dated {
+            prune(&copy)?;
+        }
+    }
+
+    let metadata = match metadata {
+        Some(metadata) if metadata.nlink() > 1 => return fs::write(&target, bytes),
+        metadata => metadata,
+    };
+
+    let name = target.file_name().ok_or(io::ErrorKind::InvalidInput)?;
+    let partial =
+        target.with_file_name(format!(".{}.{}.tmp", name.to_string_lossy(), process::id()));
+
+    match replace(&partial, &target, bytes, metadata.as_ref()) {
+        Ok(true) => Ok(()),
+        Ok(false) => {
+            let _ = fs::remove_file(&partial);
+
+            fs::write(&target, bytes)
+        }
+        Err(error) => {
+            let _ = fs::remove_file(&partial);
+
+            match error.kind() {
+                io::ErrorKind::PermissionDenied => fs::write(&target, bytes),
+                _ => Err(error),
+            }
+        }
+    }
+}
+
+fn replace(
+    partial: &Path,
+    target: &Path,
+    bytes: &[u8],
+    metadata: Option<&fs::Metadata>,
+) -> io::Result<bool> {
+    let _ = fs::remove_file(partial);
+
+    let mut file = File::options()
+        .write(true)
+        .create_new(true)
+        .mode(metadata.map_or(0o666, |metadata| metadata.mode() & 0o777))
+        .open(partial)?;
+
+    if let Some(metadata) = metadata {
+        let _ = chown(partial, Some(metadata.uid()), Some(metadata.gid()));
+
+        file.set_permissions(metadata.permissions())?;
+
+        let written = file.metadata()?;
+
+        if (written.uid(), written.gid()) != (metadata.uid(), metadata.gid()) {
+            return Ok(false);
+        }
+    }
+
+    file.write_all(bytes)?;
+    file.sync_all()?;
+
+    fs::rename(partial, target)?;
+
+    Ok(true)
+}
+
+fn resolve(path: &Path) -> io::Result<PathBuf> {
+    let mut path = path.to_path_buf();
+
+    for _ in 0..LINKS {
+        match fs::symlink_metadata(&path) {
+            Ok(metadata) if metadata.file_type().is_symlink() => {
+                let link = fs::read_link(&path)?;
+
+                path = match path.parent() {
+                    Some(directory) => directory.join(link),
+                    None => link,
+                };
+            }
+            Ok(_) => return Ok(path),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(path),
+            Err(error) => return Err(error),
+        }
+    }
+
+    Err(io::Error::other("Too many levels of symbolic links"))
+}
+
+fn prune(copy: &Path) -> io::Result<()> {
+    let (directory, name) = match (copy.parent(), copy.file_name()) {
+        (Some(directory), Some(name)) => (directory, name.to_string_lossy()),
+        _ => return Ok(()),
+    };
+
+    let prefix = match name.rsplit_once('.') {
+        Some((prefix, _)) => format!("{}.", prefix),
+        None => return Ok(()),
+    };
+
+    let mut copies = fs::read_dir(directory)?
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let name = entry.file_name().into_string().ok()?;
+            let seconds = name.strip_prefix(&prefix)?.parse::<u64>().ok()?;
+
+            Some((seconds, entry.path()))
+        })
+        .collect::<Vec<_>>();
+
+    copies.sort();
+
+    for (_, path) in copies.iter().rev().skip(DATED) {
+        fs::remove_file(path)?;
+    }
+
+    Ok(())
+}
+
+fn location(target: &Path, backup: Backup) -> Option<PathBuf> {
+    let name = target.file_name()?.to_string_lossy();
+
+    match backup {
+        Backup::Off => None,
+        Backup::Single => Some(target.with_file_name(format!("{}~", name))),
+        Backup::Dated => {
+            let seconds = SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .map_or(0, |elapsed| elapsed.as_secs());
+
+            let escaped = target.to_string_lossy().replace('/', "%");
+
+            config::directory("XDG_STATE_HOME", ".local/state").map(|directory| {
+                directory
+                    .join("backup")
+                    .join(format!("{}.{}", escaped, seconds))
+            })
+        }
+    }
+}
+
+#[test]
+fn preserve() {
+    use std::os::unix::fs::{symlink, PermissionsExt};
+
+    let directory = std::env::temp_dir().join(format!("edit-save-{}", process::id()));
+    let (file, link) = (directory.join("real.txt"), directory.join("link.txt"));
+
+    let _ = fs::remove_dir_all(&directory);
+    fs::create_dir_all(&directory).unwrap();
+    fs::write(&file, "old\n").unwrap();
+    fs::set_permissions(&file, fs::Permissions::from_mode(0o640)).unwrap();
+    symlink("real.txt", &link).unwrap();
+
+    write(&link, b"new\n", Backup::Single).unwrap();
+
+    assert!(fs::symlink_metadata(&link)
+        .unwrap()
+        .file_type()
+        .is_symlink());
+    assert_eq!(fs::read_to_string(&file).unwrap(), "new\n");
+    assert_eq!(
+        fs::read_to_string(directory.join("real.txt~")).unwrap(),
+        "old\n"
+    );
+    assert_eq!(
+        fs::metadata(&file).unwrap().permissions().mode() & 0o777,
+        0o640
+    );
+
+    write(&directory.join("fresh.txt"), b"made\n", Backup::Single).unwrap();
+
+    assert!(!directory.join("fresh.txt~").exists());
+    assert_eq!(location(&file, Backup::Off), None);
+    assert!(location(&file, Backup::Dated)
+        .is_some_and(|dated| dated.to_string_lossy().contains("%real.txt.")));
+
+    for seconds in 1..=12 {
+        fs::write(directory.join(format!("real.txt.{}", seconds)), "old\n").unwrap();
+    }
+
+    prune(&directory.join("real.txt.12")).unwrap();
+
+    assert!(!directory.join("real.txt.2").exists());
+    assert!(directory.join("real.txt.3").exists());
+    assert!(directory.join("real.txt~").exists());
+    assert!(file.exists());
+
+    fs::remove_dir_all(&directory).unwrap();
+}
@@ -0,0 +1,195 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::mpsc::{channel, Receiver, TryRecvError},
+    thread,
+};
+
+use regex::Regex;
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Match {
+    pub path: PathBuf,
+    pub line: usize,
+    pub column: usize,
+    pub offset: usize,
+    pub context: String,
+}
+
+pub struct Search {
+    root: PathBuf,
+    matches: Vec<Match>,
+    receiver: Receiver<Match>,
+    current: Option<usize>,
+    done: bool,
+}
+
+impl Search {
+    pub fn new(root: &Path, pattern: &str) -> Result<Self, regex::Error> {
+        let regex = Regex::new(pattern)?;
+
+        let (sender, receiver) = channel();
+
+        let walk = ignore::WalkBuilder::new(root).require_git(false).build();
+
+        thread::spawn(move || {
+            for entry in walk.filter_map(Result::ok) {
+                if !entry.file_type().is_some_and(|kind| kind.is_file()) {
+                    continue;
+                }
+
+                let source = match fs::read(entry.path()) {
+                    Ok(bytes) if !bytes.contains(&0) => match String::from_utf8(bytes) {
+                        Ok(source) => source,
+                        Err(_) => continue,
+                    },
+                    _ => continue,
+                };
+
+                for found in search(&regex, &source) {
+                    let found = Match {
+                        path: entry.path().into(),
+                        ..found
+                    };
+
+                    if sender.send(found).is_err() {
+                        return;
+                    }
+                }
+            }
+        });
+
+        Ok(Self {
+            root: root.into(),
+            matches: vec![],
+            receiver,
+            current: None,
+            done: false,
+        })
+    }
+
+    pub fn poll(&mut self) -> usize {
+        let before = self.matches.len();
+
+        while !self.done {
+            match self.receiver.try_recv() {
+                Ok(found) => self.matches.push(found),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => self.done = true,
+            }
+        }
+
+        self.matches.len() - before
+    }
+
+    pub fn done(&self) -> bool {
+        self.done
+    }
+
+    pub fn len(&self) -> usize {
+        self.matches.len()
+    }
+
+    pub fn listing(&self, from: usize) -> String {
+        self.matches[from..]
+            .iter()
+            .map(|found| {
+                format!(
+                    "{}:{}:{}: {}\n",
+                    found
+                        .path
+                        .strip_prefix(&self.root)
+                        .unwrap_or(&found.path)
+                        .display(),
+                    found.line,
+                    found.column,
+                    found.context
+                )
+            })
+            .collect()
+    }
+
+    pub fn select(&mut self, index: usize) -> Option<&Match> {
+        let found = self.matches.get(index)?;
+
+        self.current = Some(index);
+
+        Some(found)
+    }
+
+    pub fn next(&self) -> usize {
+        self.current.map_or(0, |current| current + 1)
+    }
+
+    pub fn previous(&self) -> Option<usize> {
+        self.current?.checked_sub(1)
+    }
+}
+
+fn search(regex: &Regex, source: &str) -> Vec<Match> {
+    let mut found = vec![];
+    let mut offset = 0;
+
+    for (index, line) in source.split_inclusive('\n').enumerate() {
+        let context = line.trim_end_matches(&['\r', '\n'][..]);
+
+        for hit in regex.find_iter(context) {
+            found.push(Match {
+                path: PathBuf::new(),
+                line: index + 1,
+                column: hit.start() + 1,
+                offset: offset + hit.start(),
+                context: context.trim().into(),
+            });
+        }
+
+        offset += line.len();
+    }
+
+    found
+}
+
+#[test]
+fn matches() {
+    let buffer = include_str!("../edit");
+
+    let found = search(&Regex::new(r"Canker\b").unwrap(), buffer);
+
+    assert_eq!(found.len(), 3);
+    assert_eq!((found[0].line, found[0].column), (3, 4));
+    assert_eq!(&buffer[found[0].offset..found[0].offset + 6], "Canker");
+    assert_eq!(found[1].context, "## Canker");
+    assert_eq!((found[2].line, found[2].column), (11, 1));
+}
+
+#[test]
+fn walk() {
+    let mut search = Search::new(Path::new(env!("CARGO_MANIFEST_DIR")), "fn walk\\(").unwrap();
+
+    while !search.done() {
+        search.poll();
+    }
+
+    let listing = search.listing(0);
+
+    assert!(listing.contains("src/search.rs:"));
+    assert!(listing.lines().all(|line| !line.starts_with("target")));
+    assert_eq!(search.next(), 0);
+    assert!(search.select(0).is_some());
+    assert_eq!(search.next(), 1);
+    assert!(search.select(search.len()).is_none());
+}
+
+#[test]
+fn first() {
+    let mut search = Search::new(Path::new(env!("CARGO_MANIFEST_DIR")), "fn first\\(").unwrap();
+
+    while !search.done() {
+        search.poll();
+    }
+
+    let first = search.matches[0].clone();
+
+    assert_eq!(search.previous(), None);
+    assert_eq!(search.select(search.next()), Some(&first));
+}
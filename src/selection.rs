@@ -0,0 +1,166 @@
+use std::ops::Range;
+
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::{cursor::Cursor, unicode};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Kind {
+    Charwise,
+    Linewise,
+    Blockwise,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Selection {
+    pub kind: Kind,
+    pub anchor: Cursor,
+}
+
+impl Selection {
+    pub fn new(kind: Kind, anchor: Cursor) -> Self {
+        Self { kind, anchor }
+    }
+
+    pub fn spans(&self, cursor: &Cursor, source: &str, tab_width: usize) -> Vec<Range<usize>> {
+        let (start, end) = match self.anchor.z() <= cursor.z() {
+            true => (&self.anchor, cursor),
+            false => (cursor, &self.anchor),
+        };
+
+        match self.kind {
+            Kind::Charwise => {
+                let span = start.z()..end.z() + end.current(source).len();
+
+                vec![span]
+            }
+            Kind::Linewise => {
+                let first = source[..start.z()].rfind('\n').map_or(0, |index| index + 1);
+                let last = source[end.z()..]
+                    .find('\n')
+                    .map_or(source.len(), |index| end.z() + index + 1);
+
+                let span = first..last;
+
+                vec![span]
+            }
+            Kind::Blockwise => {
+                let edges = [&self.anchor, cursor].map(|cursor| {
+                    let (column, width) = column(source, cursor, tab_width);
+
+                    (column, column + width)
+                });
+
+                let left = edges[0].0.min(edges[1].0);
+                let right = edges[0].1.max(edges[1].1);
+
+                let first = source[..start.z()].rfind('\n').map_or(0, |index| index + 1);
+
+                source[first..]
+                    .split_inclusive('\n')
+                    .take(end.y() - start.y() + 1)
+                    .scan(first, |offset, line| {
+                        let start = *offset;
+
+                        *offset += line.len();
+
+                        Some((start, line.trim_end_matches('\n')))
+                    })
+                    .filter_map(|(offset, line)| {
+                        let mut covered = cells(line, tab_width)
+                            .filter(|(_, column, width, _)| {
+                                *column < right && column + width > left
+                            })
+                            .map(|(index, _, _, grapheme)| index..index + grapheme.len());
+
+                        let first = covered.next()?;
+                        let last = covered.last().unwrap_or_else(|| first.clone());
+
+                        Some(offset + first.start..offset + last.end)
+                    })
+                    .collect()
+            }
+        }
+    }
+
+    pub fn edges(&self, cursor: &Cursor) -> (usize, usize) {
+        (
+            self.anchor.z().min(cursor.z()),
+            self.anchor.z().max(cursor.z()),
+        )
+    }
+}
+
+fn column(source: &str, cursor: &Cursor, tab_width: usize) -> (usize, usize) {
+    let start = cursor.z() - cursor.x();
+
+    let line = source[start..].split('\n').next().unwrap_or("");
+
+    cells(line, tab_width)
+        .find(|(index, ..)| *index >= cursor.x())
+        .map_or_else(
+            || {
+                (
+                    cells(line, tab_width).map(|(_, _, width, _)| width).sum(),
+                    1,
+                )
+            },
+            |(_, column, width, _)| (column, width),
+        )
+}
+
+fn cells(line: &str, tab_width: usize) -> impl Iterator<Item = (usize, usize, usize, &str)> {
+    line.grapheme_indices(true)
+        .scan(0, move |column, (index, grapheme)| {
+            let width = unicode::width(grapheme, *column, tab_width);
+            let cell = (index, *column, width, grapheme);
+
+            *column += width;
+
+            Some(cell)
+        })
+}
+
+#[test]
+fn spans() {
+    let source = "ab\u{e9}cd\n\u{4f60}\u{597d}xy\n\ttail\n";
+
+    let text = |spans: Vec<Range<usize>>| {
+        spans
+            .into_iter()
+            .map(|span| &source[span])
+            .collect::<Vec<_>>()
+    };
+
+    let anchor = Cursor::default().seek(source, 1);
+    let cursor = Cursor::default().seek(source, source.find('\u{597d}').unwrap());
+
+    let selection = Selection::new(Kind::Charwise, anchor.clone());
+
+    assert_eq!(
+        text(selection.spans(&cursor, source, 8)),
+        ["b\u{e9}cd\n\u{4f60}\u{597d}"]
+    );
+
+    let selection = Selection::new(Kind::Linewise, cursor.clone());
+
+    assert_eq!(
+        text(selection.spans(&anchor, source, 8)),
+        ["ab\u{e9}cd\n\u{4f60}\u{597d}xy\n"]
+    );
+
+    let selection = Selection::new(Kind::Blockwise, anchor);
+    let cursor = Cursor::default().seek(source, source.find('x').unwrap());
+
+    assert_eq!(
+        text(selection.spans(&cursor, source, 8)),
+        ["b\u{e9}cd", "\u{4f60}\u{597d}x"]
+    );
+
+    let cursor = Cursor::default().seek(source, source.find("ail").unwrap());
+
+    assert_eq!(
+        text(selection.spans(&cursor, source, 4)),
+        ["b\u{e9}cd", "\u{4f60}\u{597d}xy", "\tta"]
+    );
+}
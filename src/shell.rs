@@ -0,0 +1,79 @@
+use std::{
+    env,
+    io::{self, Write},
+    process::{Command, ExitStatus, Stdio},
+    thread,
+};
+
+#[derive(Debug, PartialEq)]
+pub struct Output {
+    pub stdout: String,
+    pub stderr: String,
+}
+
+pub fn run(command: &str, input: Option<&str>) -> Result<Output, Error> {
+    let shell = env::var("SHELL").unwrap_or_else(|_| "/bin/sh".into());
+
+    let mut child = Command::new(shell)
+        .arg("-c")
+        .arg(command)
+        .stdin(match input {
+            Some(_) => Stdio::piped(),
+            None => Stdio::null(),
+        })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    let writer = match (child.stdin.take(), input) {
+        (Some(mut stdin), Some(input)) => {
+            let input = input.to_string();
+
+            Some(thread::spawn(move || stdin.write_all(input.as_bytes())))
+        }
+        _ => None,
+    };
+
+    let output = child.wait_with_output()?;
+
+    if let Some(writer) = writer {
+        let _ = writer.join();
+    }
+
+    let text = |bytes: &[u8]| String::from_utf8_lossy(bytes).into_owned();
+
+    let (stdout, stderr) = (text(&output.stdout), text(&output.stderr));
+
+    if !output.status.success() {
+        return Err(Error::Status(output.status, stderr.trim_end().into()));
+    }
+
+    Ok(Output { stdout, stderr })
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("Io {0}")]
+    Io(#[from] io::Error),
+    #[error("Shell command failed ({0}): {1}")]
+    Status(ExitStatus, String),
+}
+
+#[test]
+fn filters() {
+    let output = run("sort", Some("b\nc\na\n")).unwrap();
+
+    assert_eq!(output.stdout, "a\nb\nc\n");
+    assert_eq!(output.stderr, "");
+
+    let output = run("echo out; echo err >&2", None).unwrap();
+
+    assert_eq!((&output.stdout[..], &output.stderr[..]), ("out\n", "err\n"));
+
+    match run("echo broken >&2; exit 3", Some("ignored")) {
+        Err(Error::Status(status, stderr)) => {
+            assert_eq!((status.code(), &stderr[..]), (Some(3), "broken"))
+        }
+        result => panic!("{:?}", result),
+    }
+}
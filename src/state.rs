@@ -0,0 +1,205 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    process,
+};
+
+use crate::{buffer::Mode, config};
+
+const LIMIT: usize = 500;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry {
+    pub offset: usize,
+    pub lens: Mode,
+    pub marks: Vec<(char, usize)>,
+}
+
+#[derive(Default)]
+pub struct State {
+    entries: Vec<(PathBuf, Entry)>,
+    file: Option<PathBuf>,
+}
+
+pub fn path() -> Option<PathBuf> {
+    config::directory("XDG_STATE_HOME", ".local/state").map(|directory| directory.join("files"))
+}
+
+pub fn session(name: &str) -> Result<PathBuf, String> {
+    if name.is_empty() {
+        return Err("No session name".into());
+    }
+
+    if name.contains(['/', '\\']) || name.starts_with('.') {
+        return Err(format!("Invalid session name: {}", name));
+    }
+
+    config::directory("XDG_STATE_HOME", ".local/state")
+        .map(|directory| directory.join("sessions").join(name))
+        .ok_or_else(|| "No state directory".into())
+}
+
+impl State {
+    pub fn load(file: PathBuf) -> Self {
+        Self {
+            entries: read(&file).unwrap_or_default(),
+            file: Some(file),
+        }
+    }
+
+    pub fn get(&self, path: &Path) -> Option<&Entry> {
+        self.entries
+            .iter()
+            .rev()
+            .find(|(existing, _)| existing == path)
+            .map(|(_, entry)| entry)
+    }
+
+    pub fn remember(&mut self, entries: impl IntoIterator<Item = (PathBuf, Entry)>) {
+        if let Some(stored) = self.file.as_deref().and_then(|file| read(file).ok()) {
+            self.entries = stored;
+        }
+
+        for (path, entry) in entries {
+            self.entries.retain(|(existing, _)| *existing != path);
+            self.entries.push((path, entry));
+        }
+
+        if self.entries.len() > LIMIT {
+            self.entries.drain(..self.entries.len() - LIMIT);
+        }
+
+        if let Some(file) = &self.file {
+            let _ = write(file, &self.listing());
+        }
+    }
+
+    fn listing(&self) -> String {
+        self.entries
+            .iter()
+            .filter(|(path, _)| !path.to_string_lossy().contains('\n'))
+            .map(|(path, entry)| format!("{}\t{}\n", format(entry), path.display()))
+            .collect()
+    }
+}
+
+fn read(file: &Path) -> io::Result<Vec<(PathBuf, Entry)>> {
+    fs::read_to_string(file).map(|source| source.lines().filter_map(parse).collect())
+}
+
+pub fn write(file: &Path, contents: &str) -> io::Result<()> {
+    if let Some(directory) = file.parent() {
+        fs::create_dir_all(directory)?;
+    }
+
+    let name = file.file_name().ok_or(io::ErrorKind::InvalidInput)?;
+    let partial = file.with_file_name(format!(".{}.{}.tmp", name.to_string_lossy(), process::id()));
+
+    fs::write(&partial, contents)?;
+    fs::rename(&partial, file)
+}
+
+fn format(entry: &Entry) -> String {
+    let marks = entry
+        .marks
+        .iter()
+        .map(|(mark, z)| format!("{}{}", mark, z))
+        .collect::<Vec<_>>()
+        .join(",");
+
+    let lens = match entry.lens {
+        Mode::Graphemes => "graphemes",
+        Mode::Lines => "lines",
+    };
+
+    format!("{}\t{}\t{}", entry.offset, lens, marks)
+}
+
+fn parse(line: &str) -> Option<(PathBuf, Entry)> {
+    let mut fields = line.splitn(4, '\t');
+
+    let offset = fields.next()?.parse().ok()?;
+
+    let lens = match fields.next()? {
+        "graphemes" => Mode::Graphemes,
+        "lines" => Mode::Lines,
+        _ => return None,
+    };
+
+    let marks = fields
+        .next()?
+        .split(',')
+        .filter(|mark| !mark.is_empty())
+        .map(|mark| {
+            let mut characters = mark.chars();
+            let name = characters.next()?;
+
+            Some((name, characters.as_str().parse().ok()?))
+        })
+        .collect::<Option<_>>()?;
+
+    let path = fields.next().filter(|path| !path.is_empty())?;
+
+    Some((
+        path.into(),
+        Entry {
+            offset,
+            lens,
+            marks,
+        },
+    ))
+}
+
+#[test]
+fn entries() {
+    let mut state = State::default();
+
+    let entry = Entry {
+        offset: 42,
+        lens: Mode::Lines,
+        marks: vec![('a', 3), ('<', 10)],
+    };
+
+    state.remember([
+        ("/notes/todo.md".into(), entry.clone()),
+        ("/notes/with\ttab".into(), entry.clone()),
+    ]);
+    state.remember([(
+        "/notes/todo.md".into(),
+        Entry {
+            offset: 7,
+            ..entry.clone()
+        },
+    )]);
+
+    let loaded = State {
+        entries: state.listing().lines().filter_map(parse).collect(),
+        file: None,
+    };
+
+    assert_eq!(loaded.get(Path::new("/notes/todo.md")).unwrap().offset, 7);
+    assert_eq!(loaded.get(Path::new("/notes/with\ttab")), Some(&entry));
+    assert_eq!(loaded.get(Path::new("/notes/other")), None);
+    assert_eq!(parse("x\tlines\t\t/a"), None);
+    assert_eq!(parse("1\tsideways\t\t/a"), None);
+
+    let file = std::env::temp_dir().join(format!("edit-state-{}", std::process::id()));
+    let _ = fs::remove_file(&file);
+
+    let (mut first, mut second) = (State::load(file.clone()), State::load(file.clone()));
+
+    first.remember([("/a".into(), entry.clone())]);
+    second.remember([("/b".into(), entry.clone())]);
+    first.remember([("/c".into(), entry.clone())]);
+
+    let merged = State::load(file.clone());
+
+    assert!(["/a", "/b", "/c"]
+        .iter()
+        .all(|path| merged.get(Path::new(path)).is_some()));
+
+    fs::remove_file(&file).unwrap();
+
+    assert!(session("work").unwrap().ends_with("sessions/work"));
+    assert!(session("../work").is_err());
+}
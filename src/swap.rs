@@ -0,0 +1,138 @@
+use std::{
+    fs::{self, File},
+    io::{self, Write},
+    os::unix::fs::{OpenOptionsExt, PermissionsExt},
+    path::{Path, PathBuf},
+};
+
+pub fn path(file: &Path) -> Option<PathBuf> {
+    let name = file.file_name()?.to_string_lossy();
+
+    Some(file.with_file_name(format!(".{}.swp", name)))
+}
+
+pub fn write(file: &Path, bytes: &[u8]) -> io::Result<()> {
+    let swap = path(file).ok_or(io::ErrorKind::InvalidInput)?;
+    let partial = swap.with_extension("swx");
+
+    let mode = fs::metadata(file).map_or(0o600, |metadata| metadata.permissions().mode() & 0o777);
+
+    let _ = fs::remove_file(&partial);
+
+    let mut swapped = File::options()
+        .write(true)
+        .create_new(true)
+        .mode(mode)
+        .open(&partial)?;
+
+    swapped.set_permissions(fs::Permissions::from_mode(mode))?;
+    swapped.write_all(bytes)?;
+
+    fs::rename(&partial, &swap)
+}
+
+pub fn read(file: &Path) -> io::Result<Option<Vec<u8>>> {
+    let swap = match path(file) {
+        Some(swap) => swap,
+        None => return Ok(None),
+    };
+
+    match fs::read(swap) {
+        Ok(bytes) => Ok(Some(bytes)),
+        Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(error) => Err(error),
+    }
+}
+
+pub fn remove(file: &Path) -> io::Result<()> {
+    let swap = match path(file) {
+        Some(swap) => swap,
+        None => return Ok(()),
+    };
+
+    match fs::remove_file(swap) {
+        Err(error) if error.kind() != io::ErrorKind::NotFound => Err(error),
+        _ => Ok(()),
+    }
+}
+
+pub fn diff(old: &str, new: &str) -> String {
+    let (old, new) = (
+        old.split_inclusive('\n').collect::<Vec<_>>(),
+        new.split_inclusive('\n').collect::<Vec<_>>(),
+    );
+
+    let prefix = old.iter().zip(&new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let (removed, added) = (
+        &old[prefix..old.len() - suffix],
+        &new[prefix..new.len() - suffix],
+    );
+
+    if removed.is_empty() && added.is_empty() {
+        return String::new();
+    }
+
+    let mut diff = format!(
+        "@@ -{},{} +{},{} @@\n",
+        prefix + 1,
+        removed.len(),
+        prefix + 1,
+        added.len()
+    );
+
+    for (sign, lines) in [('-', removed), ('+', added)] {
+        for line in lines {
+            diff.push(sign);
+            diff.push_str(line);
+
+            if !line.ends_with('\n') {
+                diff.push('\n');
+            }
+        }
+    }
+
+    diff
+}
+
+#[test]
+fn swap() {
+    assert_eq!(
+        path(Path::new("/notes/todo.md")),
+        Some(PathBuf::from("/notes/.todo.md.swp"))
+    );
+    assert_eq!(path(Path::new("/")), None);
+
+    assert_eq!(diff("a\nb\nc\n", "a\nb\nc\n"), "");
+    assert_eq!(
+        diff("a\nb\nc\nd\n", "a\nB\nx\nd\n"),
+        "@@ -2,2 +2,2 @@\n-b\n-c\n+B\n+x\n"
+    );
+    assert_eq!(diff("a\n", "a\nb"), "@@ -2,0 +2,1 @@\n+b\n");
+
+    let directory = std::env::temp_dir().join(format!("edit-private-{}", std::process::id()));
+    let file = directory.join("secret.md");
+
+    let _ = fs::remove_dir_all(&directory);
+    fs::create_dir_all(&directory).unwrap();
+    fs::write(&file, "a\n").unwrap();
+    fs::set_permissions(&file, fs::Permissions::from_mode(0o600)).unwrap();
+
+    write(&file, b"b\n").unwrap();
+
+    let swap = path(&file).unwrap();
+
+    assert_eq!(fs::read(&swap).unwrap(), b"b\n");
+    assert_eq!(
+        fs::metadata(&swap).unwrap().permissions().mode() & 0o777,
+        0o600
+    );
+
+    fs::remove_dir_all(&directory).unwrap();
+}
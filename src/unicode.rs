@@ -1,31 +1,48 @@
 use itertools::Itertools;
 use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
 
-pub fn split_line_bounds<'a>(buffer: &'a str) -> impl Iterator<Item = &'a str> {
+use crate::encoding;
+
+pub fn split_line_bounds(buffer: &str) -> impl Iterator<Item = &str> {
     UnicodeSegmentation::split_word_bound_indices(buffer)
         .peekable()
         .batching(|rest| {
             let (start, this) = rest.next()?;
             let mut stop = start + this.len();
 
-            if this == "\n" {
+            if is_break(this) {
                 return Some(&buffer[start..stop]);
             }
 
             while let Some((index, next)) = rest.peek() {
-                if *next == "\n" {
+                if is_break(next) {
                     break;
                 }
 
                 stop = *index + next.len();
 
-                drop(rest.next());
+                rest.next();
             }
 
             Some(&buffer[start..stop])
         })
 }
 
+pub fn is_break(token: &str) -> bool {
+    matches!(token, "\n" | "\r\n" | "\r")
+}
+
+pub fn width(grapheme: &str, column: usize, tab_width: usize) -> usize {
+    match grapheme {
+        "\t" => tab_width - column % tab_width,
+        grapheme => match encoding::escaped(grapheme) {
+            Some(shown) => UnicodeWidthStr::width(&shown[..]),
+            None => UnicodeWidthStr::width(grapheme).clamp(1, 2),
+        },
+    }
+}
+
 #[test]
 fn line_bounds() {
     let buffer = include_str!("../edit");
@@ -52,4 +69,19 @@ fn line_bounds() {
         Some("Canker was founded by one of the Alec Thompsons.")
     );
     assert_eq!(line_bounds.next(), Some("\n"));
+
+    let line_bounds = split_line_bounds("one\r\ntwo\rthree\n");
+
+    assert_eq!(
+        line_bounds.collect::<Vec<_>>(),
+        ["one", "\r\n", "two", "\r", "three", "\n"]
+    );
+}
+
+#[test]
+fn widths() {
+    assert_eq!(width("a", 0, 4), 1);
+    assert_eq!(width("漢", 0, 4), 2);
+    assert_eq!(width("\u{301}", 0, 4), 1);
+    assert_eq!(width("\t", 3, 4), 1);
 }
@@ -0,0 +1,144 @@
+use std::{
+    collections::{hash_map::DefaultHasher, HashSet},
+    fs,
+    hash::{Hash, Hasher},
+    io,
+    path::{Path, PathBuf},
+    sync::mpsc::{channel, Receiver, Sender},
+    time::{Duration, SystemTime},
+};
+
+use notify::{Config, Event, EventKind, PollWatcher, RecommendedWatcher, RecursiveMode, Watcher};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Stamp {
+    modified: Option<SystemTime>,
+    len: u64,
+    hash: u64,
+}
+
+impl Stamp {
+    pub fn new(metadata: &fs::Metadata, bytes: &[u8]) -> Self {
+        let mut hasher = DefaultHasher::new();
+
+        bytes.hash(&mut hasher);
+
+        Self {
+            modified: metadata.modified().ok(),
+            len: metadata.len(),
+            hash: hasher.finish(),
+        }
+    }
+
+    pub fn read(path: &Path) -> io::Result<Self> {
+        let bytes = fs::read(path)?;
+
+        Ok(Self::new(&fs::metadata(path)?, &bytes))
+    }
+
+    pub fn current(&self, path: &Path) -> io::Result<bool> {
+        let metadata = fs::metadata(path)?;
+
+        if metadata.modified().ok() == self.modified && metadata.len() == self.len {
+            return Ok(true);
+        }
+
+        Ok(Self::read(path)?.hash == self.hash)
+    }
+}
+
+pub struct Watch {
+    watcher: Option<RecommendedWatcher>,
+    fallback: Option<PollWatcher>,
+    sender: Sender<notify::Result<Event>>,
+    receiver: Receiver<notify::Result<Event>>,
+    directories: HashSet<PathBuf>,
+}
+
+impl Watch {
+    pub fn new() -> Self {
+        let (sender, receiver) = channel();
+
+        Self {
+            watcher: RecommendedWatcher::new(sender.clone(), Config::default()).ok(),
+            fallback: None,
+            sender,
+            receiver,
+            directories: HashSet::new(),
+        }
+    }
+
+    pub fn add(&mut self, file: &Path) {
+        let directory = match file.parent() {
+            Some(directory) if !self.directories.contains(directory) => directory,
+            _ => return,
+        };
+
+        let watched = self.watcher.as_mut().is_some_and(|watcher| {
+            watcher
+                .watch(directory, RecursiveMode::NonRecursive)
+                .is_ok()
+        });
+
+        if !watched {
+            let sender = self.sender.clone();
+
+            let fallback = match self.fallback.as_mut() {
+                Some(fallback) => fallback,
+                None => match PollWatcher::new(
+                    sender,
+                    Config::default().with_poll_interval(Duration::from_secs(2)),
+                ) {
+                    Ok(fallback) => self.fallback.insert(fallback),
+                    Err(_) => return,
+                },
+            };
+
+            if fallback
+                .watch(directory, RecursiveMode::NonRecursive)
+                .is_err()
+            {
+                return;
+            }
+        }
+
+        self.directories.insert(directory.into());
+    }
+
+    pub fn poll(&mut self) -> HashSet<PathBuf> {
+        self.receiver
+            .try_iter()
+            .filter_map(Result::ok)
+            .filter(|event| {
+                matches!(
+                    event.kind,
+                    EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+                )
+            })
+            .flat_map(|event| event.paths)
+            .collect()
+    }
+}
+
+#[test]
+fn stamps() {
+    let path = Path::new(file!());
+    let stamp = Stamp::read(path).unwrap();
+
+    assert!(stamp.current(path).unwrap());
+
+    let touched = Stamp {
+        modified: None,
+        ..stamp
+    };
+
+    assert!(touched.current(path).unwrap());
+
+    let changed = Stamp {
+        modified: None,
+        hash: 0,
+        ..stamp
+    };
+
+    assert!(!changed.current(path).unwrap());
+}